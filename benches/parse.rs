@@ -0,0 +1,23 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+
+use stock_ticker::types::StockPrice;
+
+const PAYLOAD: &[u8] =
+    br#"{"symbol":"AAPL","price":151.2,"timestamp":"2025-03-12T14:30:00.123456789+00:00"}"#;
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.bench_function("from_utf8_lossy + from_str", |b| {
+        b.iter(|| {
+            let payload = String::from_utf8_lossy(black_box(PAYLOAD));
+            serde_json::from_str::<StockPrice>(&payload).unwrap()
+        })
+    });
+    group.bench_function("from_bytes", |b| {
+        b.iter(|| StockPrice::from_bytes(black_box(PAYLOAD)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
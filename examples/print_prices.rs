@@ -0,0 +1,24 @@
+//! Prints the next ten prices from the tick subject and exits.
+//!
+//! ```bash
+//! cargo run --example print_prices
+//! ```
+
+use futures::StreamExt;
+
+use stock_ticker::subscriber::{StockSubscriber, SubscriberOptions};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let prices = StockSubscriber::connect(SubscriberOptions::new()).await?;
+    let mut prices = Box::pin(prices.take(10));
+
+    while let Some(price) = prices.next().await {
+        match price {
+            Ok(stock) => println!("{} {:.2} at {}", stock.symbol, stock.price, stock.timestamp),
+            Err(e) => eprintln!("skipping message: {}", e),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,369 @@
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+use crate::types::StockPrice;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Comparison {
+    Above,
+    Below,
+    AtOrAbove,
+    AtOrBelow,
+    /// The price moved from one side of the threshold to the other.
+    Crosses,
+}
+
+impl Comparison {
+    fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Above => ">",
+            Comparison::Below => "<",
+            Comparison::AtOrAbove => ">=",
+            Comparison::AtOrBelow => "<=",
+            Comparison::Crosses => "crosses",
+        }
+    }
+}
+
+/// Symbol of a rule that applies to every symbol.
+pub const ANY_SYMBOL: &str = "*";
+
+/// A single alert rule such as `TSLA>300` or `AAPL crosses 150`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlertRule {
+    pub symbol: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.comparison {
+            Comparison::Crosses => write!(f, "{} crosses {}", self.symbol, self.threshold),
+            op => write!(f, "{}{}{}", self.symbol, op.symbol(), self.threshold),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlertParseError {
+    MissingOperator(String),
+    InvalidSymbol(String),
+    InvalidThreshold(String),
+}
+
+impl fmt::Display for AlertParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertParseError::MissingOperator(rule) => write!(
+                f,
+                "alert rule '{}' has no operator (expected >, <, >=, <= or crosses)",
+                rule
+            ),
+            AlertParseError::InvalidSymbol(rule) => {
+                write!(f, "alert rule '{}' has a missing or invalid symbol", rule)
+            }
+            AlertParseError::InvalidThreshold(rule) => {
+                write!(f, "alert rule '{}' has an invalid threshold", rule)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AlertParseError {}
+
+impl AlertRule {
+    /// Parses a `--alert-above`/`--alert-below` threshold: a bare price for
+    /// every symbol, or `SYMBOL=PRICE` for one.
+    pub fn threshold(spec: &str, comparison: Comparison) -> Result<Self, AlertParseError> {
+        let (symbol, threshold) = match spec.split_once('=') {
+            Some((symbol, threshold)) => (symbol.trim(), threshold),
+            None => (ANY_SYMBOL, spec),
+        };
+        if symbol.is_empty() || !(symbol == ANY_SYMBOL || symbol.chars().all(valid_symbol_char)) {
+            return Err(AlertParseError::InvalidSymbol(spec.to_string()));
+        }
+        let threshold = threshold
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|t| t.is_finite())
+            .ok_or_else(|| AlertParseError::InvalidThreshold(spec.to_string()))?;
+        Ok(AlertRule {
+            symbol: symbol.to_uppercase(),
+            comparison,
+            threshold,
+        })
+    }
+
+    fn applies_to(&self, symbol: &str) -> bool {
+        self.symbol == ANY_SYMBOL || self.symbol.eq_ignore_ascii_case(symbol)
+    }
+}
+
+fn valid_symbol_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')
+}
+
+impl FromStr for AlertRule {
+    type Err = AlertParseError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let trimmed = rule.trim();
+
+        // Two-character operators must be tried before their one-character prefixes
+        let operators = [
+            (">=", Comparison::AtOrAbove),
+            ("<=", Comparison::AtOrBelow),
+            (">", Comparison::Above),
+            ("<", Comparison::Below),
+            (" crosses ", Comparison::Crosses),
+        ];
+
+        let (symbol, comparison, threshold) = operators
+            .iter()
+            .find_map(|(op, comparison)| {
+                trimmed
+                    .split_once(op)
+                    .map(|(symbol, threshold)| (symbol, *comparison, threshold))
+            })
+            .ok_or_else(|| AlertParseError::MissingOperator(rule.to_string()))?;
+
+        let symbol = symbol.trim();
+        if symbol.is_empty() || !symbol.chars().all(valid_symbol_char) {
+            return Err(AlertParseError::InvalidSymbol(rule.to_string()));
+        }
+
+        let threshold = threshold
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|t| t.is_finite())
+            .ok_or_else(|| AlertParseError::InvalidThreshold(rule.to_string()))?;
+
+        Ok(AlertRule {
+            symbol: symbol.to_uppercase(),
+            comparison,
+            threshold,
+        })
+    }
+}
+
+/// An alert that fired for an incoming price.
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub rule: String,
+    pub symbol: String,
+    pub price: f64,
+    pub timestamp: String,
+}
+
+/// Evaluates rules against incoming prices with hysteresis: a rule fires when
+/// its condition becomes true and does not fire again until it has been false.
+/// Rules for every symbol track each symbol separately.
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    /// Per rule, the symbols whose condition currently holds.
+    active: Vec<HashSet<String>>,
+    last_price: HashMap<String, f64>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        let active = vec![HashSet::new(); rules.len()];
+        AlertEngine {
+            rules,
+            active,
+            last_price: HashMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn evaluate(&mut self, stock: &StockPrice) -> Vec<Alert> {
+        let previous = self.last_price.insert(stock.symbol.clone(), stock.price);
+        let mut fired = Vec::new();
+
+        for (rule, active) in self.rules.iter().zip(self.active.iter_mut()) {
+            if !rule.applies_to(&stock.symbol) {
+                continue;
+            }
+
+            let holds = match rule.comparison {
+                Comparison::Above => stock.price > rule.threshold,
+                Comparison::Below => stock.price < rule.threshold,
+                Comparison::AtOrAbove => stock.price >= rule.threshold,
+                Comparison::AtOrBelow => stock.price <= rule.threshold,
+                Comparison::Crosses => previous
+                    .is_some_and(|prev| (prev < rule.threshold) != (stock.price < rule.threshold)),
+            };
+
+            let was_active = if holds {
+                !active.insert(stock.symbol.clone())
+            } else {
+                active.remove(&stock.symbol)
+            };
+            if holds && !was_active {
+                fired.push(Alert {
+                    rule: rule.to_string(),
+                    symbol: stock.symbol.clone(),
+                    price: stock.price,
+                    timestamp: stock.timestamp.clone(),
+                });
+            }
+        }
+
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(symbol: &str, price: f64) -> StockPrice {
+        StockPrice {
+            symbol: symbol.to_string(),
+            price,
+            ..StockPrice::default()
+        }
+    }
+
+    fn fires(engine: &mut AlertEngine, symbol: &str, price: f64) -> bool {
+        !engine.evaluate(&tick(symbol, price)).is_empty()
+    }
+
+    #[test]
+    fn parses_each_operator() {
+        let cases = [
+            ("TSLA>300", Comparison::Above),
+            ("TSLA<300", Comparison::Below),
+            ("TSLA>=300", Comparison::AtOrAbove),
+            ("TSLA<=300", Comparison::AtOrBelow),
+            ("TSLA crosses 300", Comparison::Crosses),
+        ];
+        for (rule, comparison) in cases {
+            let parsed: AlertRule = rule.parse().unwrap();
+            assert_eq!(parsed.symbol, "TSLA");
+            assert_eq!(parsed.comparison, comparison, "{}", rule);
+            assert_eq!(parsed.threshold, 300.0);
+        }
+    }
+
+    #[test]
+    fn lowercase_symbol_is_normalized() {
+        let rule: AlertRule = " brk.b > 1.5 ".parse().unwrap();
+        assert_eq!(rule.symbol, "BRK.B");
+        assert_eq!(rule.threshold, 1.5);
+    }
+
+    #[test]
+    fn missing_operator_is_rejected() {
+        for rule in ["TSLA300", "TSLA = 300", "", "TSLA crosses"] {
+            assert_eq!(
+                rule.parse::<AlertRule>(),
+                Err(AlertParseError::MissingOperator(rule.to_string())),
+                "{:?}",
+                rule
+            );
+        }
+    }
+
+    #[test]
+    fn empty_or_invalid_symbol_is_rejected() {
+        for rule in [">300", "  <300", "TS LA>300", "*>300"] {
+            assert_eq!(
+                rule.parse::<AlertRule>(),
+                Err(AlertParseError::InvalidSymbol(rule.to_string())),
+                "{:?}",
+                rule
+            );
+        }
+    }
+
+    #[test]
+    fn bad_threshold_is_rejected() {
+        for rule in ["TSLA>abc", "TSLA>", "TSLA>NaN", "TSLA<inf", "TSLA>=1e400"] {
+            assert_eq!(
+                rule.parse::<AlertRule>(),
+                Err(AlertParseError::InvalidThreshold(rule.to_string())),
+                "{:?}",
+                rule
+            );
+        }
+    }
+
+    #[test]
+    fn threshold_specs_parse() {
+        let any = AlertRule::threshold("250", Comparison::Above).unwrap();
+        assert_eq!(any.symbol, ANY_SYMBOL);
+        let one = AlertRule::threshold("aapl=150", Comparison::Below).unwrap();
+        assert_eq!(one.symbol, "AAPL");
+        assert_eq!(
+            AlertRule::threshold("=150", Comparison::Below),
+            Err(AlertParseError::InvalidSymbol("=150".to_string()))
+        );
+        assert_eq!(
+            AlertRule::threshold("AAPL=x", Comparison::Below),
+            Err(AlertParseError::InvalidThreshold("AAPL=x".to_string()))
+        );
+    }
+
+    #[test]
+    fn strict_and_inclusive_comparisons_differ_at_the_threshold() {
+        let mut above = AlertEngine::new(vec!["TSLA>300".parse().unwrap()]);
+        let mut at_or_above = AlertEngine::new(vec!["TSLA>=300".parse().unwrap()]);
+        assert!(!fires(&mut above, "TSLA", 300.0));
+        assert!(fires(&mut at_or_above, "TSLA", 300.0));
+    }
+
+    #[test]
+    fn first_tick_past_threshold_fires() {
+        let mut engine = AlertEngine::new(vec!["TSLA>300".parse().unwrap()]);
+        assert!(fires(&mut engine, "TSLA", 310.0));
+    }
+
+    #[test]
+    fn rearms_only_after_crossing_back() {
+        let mut engine = AlertEngine::new(vec!["TSLA>300".parse().unwrap()]);
+        assert!(fires(&mut engine, "TSLA", 310.0));
+        assert!(!fires(&mut engine, "TSLA", 320.0));
+        assert!(!fires(&mut engine, "TSLA", 305.0));
+        assert!(!fires(&mut engine, "TSLA", 290.0));
+        assert!(fires(&mut engine, "TSLA", 301.0));
+    }
+
+    #[test]
+    fn crosses_needs_a_previous_price() {
+        let mut engine = AlertEngine::new(vec!["AAPL crosses 150".parse().unwrap()]);
+        assert!(!fires(&mut engine, "AAPL", 160.0));
+        assert!(fires(&mut engine, "AAPL", 140.0));
+        assert!(!fires(&mut engine, "AAPL", 141.0));
+        assert!(fires(&mut engine, "AAPL", 151.0));
+    }
+
+    #[test]
+    fn state_is_tracked_per_symbol() {
+        let rule = AlertRule::threshold("300", Comparison::Above).unwrap();
+        let mut engine = AlertEngine::new(vec![rule]);
+        assert!(fires(&mut engine, "TSLA", 310.0));
+        assert!(fires(&mut engine, "MSFT", 310.0));
+        assert!(!fires(&mut engine, "TSLA", 311.0));
+        assert!(!fires(&mut engine, "MSFT", 290.0));
+        assert!(!fires(&mut engine, "TSLA", 312.0));
+        assert!(fires(&mut engine, "MSFT", 310.0));
+    }
+
+    #[test]
+    fn rules_only_apply_to_their_symbol() {
+        let mut engine = AlertEngine::new(vec!["tsla>300".parse().unwrap()]);
+        assert!(!fires(&mut engine, "MSFT", 400.0));
+        assert!(fires(&mut engine, "tsla", 400.0));
+    }
+}
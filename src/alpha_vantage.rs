@@ -0,0 +1,222 @@
+use serde::Deserialize;
+use std::{collections::HashMap, fmt, time::Duration};
+use tokio::time::Instant;
+
+use crate::config::env_value;
+use crate::price_source::PriceSource;
+
+/// Environment variable holding the API key.
+pub const API_KEY_ENV: &str = "ALPHA_VANTAGE_API_KEY";
+
+/// Environment variable overriding the query endpoint, e.g. for a proxy.
+pub const BASE_URL_ENV: &str = "ALPHA_VANTAGE_URL";
+
+/// Environment variable overriding `DEFAULT_MIN_INTERVAL`, e.g. "1s" for a
+/// premium key.
+pub const MIN_INTERVAL_ENV: &str = "ALPHA_VANTAGE_MIN_INTERVAL";
+
+pub const DEFAULT_BASE_URL: &str = "https://www.alphavantage.co/query";
+
+/// Gap between requests that stays within the free tier's 5 per minute.
+pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(12);
+
+#[derive(Debug)]
+pub enum AlphaVantageError {
+    /// `ALPHA_VANTAGE_API_KEY` is unset or empty.
+    MissingKey,
+    /// `ALPHA_VANTAGE_MIN_INTERVAL` is not a duration.
+    InvalidMinInterval(humantime::DurationError),
+    Request(reqwest::Error),
+    /// The API answered with an error or rate-limit message instead of a
+    /// quote.
+    Api(String),
+    /// The API returned no usable quote, typically for an unknown symbol.
+    NoQuote {
+        symbol: String,
+    },
+    /// The next request is not due yet and the symbol has no earlier quote.
+    Throttled {
+        symbol: String,
+    },
+}
+
+impl fmt::Display for AlphaVantageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlphaVantageError::MissingKey => write!(f, "{} is not set", API_KEY_ENV),
+            AlphaVantageError::InvalidMinInterval(e) => {
+                write!(f, "invalid {}: {}", MIN_INTERVAL_ENV, e)
+            }
+            AlphaVantageError::Request(e) => write!(f, "Alpha Vantage request failed: {}", e),
+            AlphaVantageError::Api(message) => write!(f, "Alpha Vantage: {}", message),
+            AlphaVantageError::NoQuote { symbol } => {
+                write!(f, "Alpha Vantage has no quote for {}", symbol)
+            }
+            AlphaVantageError::Throttled { symbol } => {
+                write!(f, "no Alpha Vantage quote for {} yet (throttled)", symbol)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AlphaVantageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AlphaVantageError::InvalidMinInterval(e) => Some(e),
+            AlphaVantageError::Request(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Reply to `function=GLOBAL_QUOTE`. Errors and rate limiting come back as
+/// a 200 with one of the message fields instead of a quote.
+#[derive(Deserialize)]
+struct QuoteResponse {
+    #[serde(rename = "Global Quote")]
+    quote: Option<GlobalQuote>,
+    #[serde(rename = "Error Message")]
+    error: Option<String>,
+    #[serde(rename = "Note")]
+    note: Option<String>,
+    #[serde(rename = "Information")]
+    information: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GlobalQuote {
+    /// A decimal string such as `"123.4500"`; absent for unknown symbols.
+    #[serde(rename = "05. price")]
+    price: Option<String>,
+}
+
+/// Live quotes from the Alpha Vantage REST API.
+///
+/// Requests are spaced at least `min_interval` apart. Between them each
+/// symbol's last quote is returned again, and each free slot goes to the
+/// symbol asked for so far that has waited longest since its last request,
+/// so every symbol is refreshed in turn and a failing one cannot starve
+/// the rest.
+#[derive(Debug)]
+pub struct AlphaVantageSource {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    min_interval: Duration,
+    next_request: Instant,
+    /// Symbols in the order first asked for.
+    symbols: Vec<String>,
+    quotes: HashMap<String, f64>,
+    requested_at: HashMap<String, Instant>,
+}
+
+impl AlphaVantageSource {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        AlphaVantageSource {
+            http: reqwest::Client::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_key: api_key.into(),
+            min_interval: DEFAULT_MIN_INTERVAL,
+            next_request: Instant::now(),
+            symbols: Vec::new(),
+            quotes: HashMap::new(),
+            requested_at: HashMap::new(),
+        }
+    }
+
+    /// Reads the API key from `ALPHA_VANTAGE_API_KEY`, plus the endpoint and
+    /// request spacing from `ALPHA_VANTAGE_URL` and
+    /// `ALPHA_VANTAGE_MIN_INTERVAL` when set.
+    pub fn from_env() -> Result<Self, AlphaVantageError> {
+        let api_key = env_value(API_KEY_ENV).ok_or(AlphaVantageError::MissingKey)?;
+        let mut source = AlphaVantageSource::new(api_key);
+        if let Some(base_url) = env_value(BASE_URL_ENV) {
+            source = source.base_url(base_url);
+        }
+        if let Some(interval) = env_value(MIN_INTERVAL_ENV) {
+            let interval = humantime::parse_duration(&interval)
+                .map_err(AlphaVantageError::InvalidMinInterval)?;
+            source = source.min_interval(interval);
+        }
+        Ok(source)
+    }
+
+    /// Spaces requests further apart, or closer for a premium key.
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// The symbol the next request should refresh: one never requested,
+    /// else the one requested longest ago.
+    fn due(&self) -> Option<&str> {
+        self.symbols
+            .iter()
+            .min_by_key(|symbol| self.requested_at.get(*symbol))
+            .map(String::as_str)
+    }
+
+    async fn fetch(&self, symbol: &str) -> Result<f64, AlphaVantageError> {
+        let response: QuoteResponse = self
+            .http
+            .get(&self.base_url)
+            .query(&[
+                ("function", "GLOBAL_QUOTE"),
+                ("symbol", symbol),
+                ("apikey", &self.api_key),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(request_error)?
+            .json()
+            .await
+            .map_err(request_error)?;
+        if let Some(message) = response.error.or(response.note).or(response.information) {
+            return Err(AlphaVantageError::Api(message));
+        }
+        response
+            .quote
+            .and_then(|quote| quote.price)
+            .and_then(|price| price.trim().parse::<f64>().ok())
+            .filter(|price| price.is_finite() && *price > 0.0)
+            .ok_or_else(|| AlphaVantageError::NoQuote {
+                symbol: symbol.to_string(),
+            })
+    }
+}
+
+impl PriceSource for AlphaVantageSource {
+    type Error = AlphaVantageError;
+
+    async fn price(&mut self, symbol: &str) -> Result<f64, AlphaVantageError> {
+        if !self.symbols.iter().any(|known| known == symbol) {
+            self.symbols.push(symbol.to_string());
+        }
+        let cached = self.quotes.get(symbol).copied();
+        if Instant::now() < self.next_request || self.due() != Some(symbol) {
+            return cached.ok_or_else(|| AlphaVantageError::Throttled {
+                symbol: symbol.to_string(),
+            });
+        }
+
+        // A failed request still uses up its slot, so errors are throttled too
+        let now = Instant::now();
+        self.next_request = now + self.min_interval;
+        self.requested_at.insert(symbol.to_string(), now);
+        let price = self.fetch(symbol).await?;
+        self.quotes.insert(symbol.to_string(), price);
+        Ok(price)
+    }
+}
+
+/// The request URL carries the API key, so it is dropped from errors
+/// before they can be logged.
+fn request_error(e: reqwest::Error) -> AlphaVantageError {
+    AlphaVantageError::Request(e.without_url())
+}
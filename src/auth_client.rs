@@ -0,0 +1,386 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+use tokio::time::{Duration, sleep};
+
+pub const DEFAULT_AUTH_URL: &str = "http://localhost:3001";
+
+/// Retries after the first failed attempt before giving up.
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Delay before the first retry; doubled after every further failure.
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+#[derive(Debug)]
+pub enum AuthError {
+    /// The auth service could not be reached or kept failing after every retry.
+    Unreachable {
+        attempts: u32,
+        source: reqwest::Error,
+    },
+    /// The auth service refused the request itself with a 4xx status.
+    Rejected { status: reqwest::StatusCode },
+    /// The auth service answered with something other than `true` or `false`.
+    InvalidResponse(reqwest::Error),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Unreachable { attempts, source } => write!(
+                f,
+                "auth service unreachable after {} attempts: {}",
+                attempts, source
+            ),
+            AuthError::Rejected { status } => {
+                write!(f, "auth service rejected the request: {}", status)
+            }
+            AuthError::InvalidResponse(e) => write!(f, "invalid auth service response: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AuthError::Unreachable { source, .. } => Some(source),
+            AuthError::Rejected { .. } => None,
+            AuthError::InvalidResponse(e) => Some(e),
+        }
+    }
+}
+
+/// HTTP client for the auth service's `/is-authorized` check.
+#[derive(Clone)]
+pub struct AuthClient {
+    base_url: String,
+    http: reqwest::Client,
+    max_retries: u32,
+    initial_backoff: Duration,
+    quiet: bool,
+}
+
+impl AuthClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        AuthClient {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            quiet: false,
+        }
+    }
+
+    pub fn with_retries(mut self, max_retries: u32, initial_backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Retries without logging each failed attempt, e.g. while a TUI owns the
+    /// terminal. The final error is still returned.
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Asks the auth service whether `email` is authorized.
+    ///
+    /// Connection failures and server errors are retried with exponential
+    /// backoff; an explicit answer from the service is returned as-is, and a
+    /// 4xx status is returned at once as `AuthError::Rejected`.
+    pub async fn is_authorized(&self, email: &str) -> Result<bool, AuthError> {
+        let url = format!("{}/is-authorized", self.base_url);
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            let error = match self.http.get(&url).query(&[("email", email)]).send().await {
+                Ok(response) if response.status().is_client_error() => {
+                    return Err(AuthError::Rejected {
+                        status: response.status(),
+                    });
+                }
+                Ok(response) => match response.error_for_status() {
+                    Ok(response) => {
+                        return response
+                            .json::<bool>()
+                            .await
+                            .map_err(AuthError::InvalidResponse);
+                    }
+                    Err(e) => e,
+                },
+                Err(e) => e,
+            };
+
+            if attempt > self.max_retries {
+                return Err(AuthError::Unreachable {
+                    attempts: attempt,
+                    source: error,
+                });
+            }
+            if !self.quiet {
+                eprintln!(
+                    "⚠️  Auth check failed (attempt {}/{}): {}. Retrying in {:?}...",
+                    attempt,
+                    self.max_retries + 1,
+                    error,
+                    backoff
+                );
+            }
+            sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+}
+
+/// NATS subject on which the auth service announces revoked emails.
+pub const REVOCATION_SUBJECT: &str = "auth.revoked";
+
+/// Extracts the revoked email from an `auth.revoked` payload, which is either
+/// a JSON object like `{"email": "user@example.com"}` or the bare address.
+pub fn revoked_email(payload: &[u8]) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct Revocation {
+        email: String,
+    }
+
+    let email = match serde_json::from_slice::<Revocation>(payload) {
+        Ok(revocation) => revocation.email,
+        Err(_) => String::from_utf8_lossy(payload).trim().to_string(),
+    };
+    (!email.is_empty()).then_some(email)
+}
+
+#[derive(Debug)]
+pub enum CredentialsError {
+    /// The file could not be read, including when it does not exist.
+    Io { path: PathBuf, source: io::Error },
+    /// The file holds no email address.
+    Empty { path: PathBuf },
+}
+
+impl fmt::Display for CredentialsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialsError::Io { path, source } => write!(
+                f,
+                "cannot read credentials file {}: {}",
+                path.display(),
+                source
+            ),
+            CredentialsError::Empty { path } => {
+                write!(f, "credentials file {} is empty", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for CredentialsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CredentialsError::Io { source, .. } => Some(source),
+            CredentialsError::Empty { .. } => None,
+        }
+    }
+}
+
+/// Identity read from a `--credentials` file.
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub email: String,
+    /// Permission bits of the file when group or others can read it, so the
+    /// caller can warn. Always `None` off Unix.
+    pub exposed_mode: Option<u32>,
+}
+
+/// Reads a credentials file: the email address on the first non-blank line,
+/// with surrounding whitespace trimmed. Later lines are reserved for an API
+/// token.
+pub fn load_credentials(path: &Path) -> Result<Credentials, CredentialsError> {
+    let io_error = |source| CredentialsError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let contents = fs::read_to_string(path).map_err(io_error)?;
+    let email = contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .ok_or_else(|| CredentialsError::Empty {
+            path: path.to_path_buf(),
+        })?
+        .to_string();
+
+    #[cfg(unix)]
+    let exposed_mode = {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(path).map_err(io_error)?.permissions().mode() & 0o777;
+        (mode & 0o044 != 0).then_some(mode)
+    };
+    #[cfg(not(unix))]
+    let exposed_mode = None;
+
+    Ok(Credentials {
+        email,
+        exposed_mode,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, http::StatusCode, routing::get};
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    /// Serves `responses` to `/is-authorized` in order, repeating the last
+    /// one, and counts the requests.
+    async fn auth_service(
+        responses: Vec<(StatusCode, &'static str)>,
+    ) -> (AuthClient, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = Router::new().route(
+            "/is-authorized",
+            get(move || {
+                let hit = counter.fetch_add(1, Ordering::SeqCst);
+                let response = responses[hit.min(responses.len() - 1)];
+                async move { response }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let client = AuthClient::new(url).with_retries(2, Duration::from_millis(1));
+        (client, hits)
+    }
+
+    #[tokio::test]
+    async fn answer_is_returned() {
+        let (client, _) = auth_service(vec![(StatusCode::OK, "true")]).await;
+        assert!(client.is_authorized("user@example.com").await.unwrap());
+        let (client, _) = auth_service(vec![(StatusCode::OK, "false")]).await;
+        assert!(!client.is_authorized("user@example.com").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn client_error_is_not_retried() {
+        let (client, hits) = auth_service(vec![(StatusCode::BAD_REQUEST, "")]).await;
+        match client.is_authorized("user@example.com").await {
+            Err(AuthError::Rejected { status }) => assert_eq!(status, StatusCode::BAD_REQUEST),
+            other => panic!("expected a rejection, got {:?}", other),
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn server_error_is_retried() {
+        let (client, hits) = auth_service(vec![
+            (StatusCode::SERVICE_UNAVAILABLE, ""),
+            (StatusCode::OK, "true"),
+        ])
+        .await;
+        assert!(client.is_authorized("user@example.com").await.unwrap());
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn persistent_server_error_is_unreachable() {
+        let (client, hits) = auth_service(vec![(StatusCode::INTERNAL_SERVER_ERROR, "")]).await;
+        assert!(matches!(
+            client.is_authorized("user@example.com").await,
+            Err(AuthError::Unreachable { attempts: 3, .. })
+        ));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn connection_failure_is_unreachable() {
+        // Nothing listens on a port once its listener is dropped
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let client = AuthClient::new(url).with_retries(1, Duration::from_millis(1));
+        assert!(matches!(
+            client.is_authorized("user@example.com").await,
+            Err(AuthError::Unreachable { attempts: 2, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn non_boolean_answer_is_invalid() {
+        let (client, hits) = auth_service(vec![(StatusCode::OK, "yes")]).await;
+        assert!(matches!(
+            client.is_authorized("user@example.com").await,
+            Err(AuthError::InvalidResponse(_))
+        ));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    fn credentials_file(contents: &str) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials");
+        fs::write(&path, contents).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing");
+        match load_credentials(&path) {
+            Err(CredentialsError::Io {
+                path: reported,
+                source,
+            }) => {
+                assert_eq!(reported, path);
+                assert_eq!(source.kind(), io::ErrorKind::NotFound);
+            }
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn empty_file_is_rejected() {
+        for contents in ["", "\n", "  \n\t\n"] {
+            let (_dir, path) = credentials_file(contents);
+            assert!(matches!(
+                load_credentials(&path),
+                Err(CredentialsError::Empty { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn surrounding_whitespace_is_trimmed() {
+        let (_dir, path) = credentials_file("\n  user@example.com \r\n");
+        assert_eq!(load_credentials(&path).unwrap().email, "user@example.com");
+    }
+
+    #[test]
+    fn later_lines_are_ignored() {
+        let (_dir, path) = credentials_file("user@example.com\ntoken-for-later\n");
+        assert_eq!(load_credentials(&path).unwrap().email, "user@example.com");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn readable_by_others_is_reported() {
+        use std::os::unix::fs::PermissionsExt;
+        let (_dir, path) = credentials_file("user@example.com\n");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(load_credentials(&path).unwrap().exposed_mode, None);
+        // Write or execute access alone does not expose the email
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o622)).unwrap();
+        assert_eq!(load_credentials(&path).unwrap().exposed_mode, None);
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        assert_eq!(load_credentials(&path).unwrap().exposed_mode, Some(0o640));
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o604)).unwrap();
+        assert_eq!(load_credentials(&path).unwrap().exposed_mode, Some(0o604));
+    }
+}
@@ -0,0 +1,426 @@
+use axum::{
+    Router,
+    extract::{Json, Path, Query, State},
+    http::{HeaderMap, Request, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::TraceLayer;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::types::StockPrice;
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, IntoParams)]
+pub struct Registration {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiError {
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Health {
+    pub status: &'static str,
+    pub authorized_emails: usize,
+}
+
+/// Header naming a registration attempt, so a client can retry it safely.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// How long a registration's result is replayed for its idempotency key.
+pub const IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// The set of authorized emails, shared by every handler.
+#[derive(Clone)]
+pub struct AppState {
+    authorized_emails: Arc<Mutex<HashSet<String>>>,
+    /// Results of recent registrations, by idempotency key.
+    idempotency_keys: Arc<Mutex<HashMap<String, Replay>>>,
+    idempotency_ttl: Duration,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        AppState {
+            authorized_emails: Arc::default(),
+            idempotency_keys: Arc::default(),
+            idempotency_ttl: IDEMPOTENCY_KEY_TTL,
+        }
+    }
+}
+
+/// What a registration did, kept so a retry with the same key gets the
+/// same answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Registered {
+    Created,
+    AlreadyRegistered,
+}
+
+#[derive(Debug, Clone)]
+struct Replay {
+    stored_at: Instant,
+    email: String,
+    outcome: Registered,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replays registrations for `ttl` instead of `IDEMPOTENCY_KEY_TTL`.
+    pub fn with_idempotency_ttl(mut self, ttl: Duration) -> Self {
+        self.idempotency_ttl = ttl;
+        self
+    }
+
+    /// Locks the email set, recovering the guard if a previous holder panicked
+    /// so a single panic cannot take down every later request.
+    fn emails(&self) -> MutexGuard<'_, HashSet<String>> {
+        recover(&self.authorized_emails, "authorized emails")
+    }
+
+    /// Locks the idempotency keys after dropping expired ones.
+    fn idempotency_keys(&self) -> MutexGuard<'_, HashMap<String, Replay>> {
+        let mut keys = recover(&self.idempotency_keys, "idempotency keys");
+        let ttl = self.idempotency_ttl;
+        keys.retain(|_, replay| replay.stored_at.elapsed() < ttl);
+        keys
+    }
+}
+
+fn recover<'a, T>(mutex: &'a Mutex<T>, what: &str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        tracing::warn!("Recovered poisoned lock on {}", what);
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(register, get_email, is_authorized, list_emails, health),
+    components(schemas(Registration, ApiError, Health, StockPrice)),
+    info(
+        title = "Auth Service",
+        description = "Email-based authorization for the stock ticker system"
+    )
+)]
+pub struct ApiDoc;
+
+/// Header carrying each request's ID, taken from the client when present and
+/// generated otherwise, and echoed on the response.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Bytes escaped when an email becomes a path segment; the usual email
+/// punctuation stays readable.
+const EMAIL_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'@')
+    .remove(b'.')
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'+');
+
+/// Path of the resource for a registered email, e.g. `/emails/user@example.com`.
+pub fn email_location(email: &str) -> String {
+    format!("/emails/{}", utf8_percent_encode(email, EMAIL_SEGMENT))
+}
+
+/// Every route of the auth service, with the OpenAPI document, Swagger UI
+/// and request ID and tracing layers.
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/register", post(register))
+        .route("/emails/{email}", get(get_email))
+        .route("/is-authorized", get(is_authorized))
+        .route("/list-emails", get(list_emails))
+        .route("/health", get(health))
+        .merge(SwaggerUi::new("/swagger-ui").url("/openapi.json", ApiDoc::openapi()))
+        .with_state(state)
+        // The last layer added runs first, so the ID is set before the span reads it
+        .layer(PropagateRequestIdLayer::new(
+            REQUEST_ID_HEADER.parse().unwrap(),
+        ))
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .layer(SetRequestIdLayer::new(
+            REQUEST_ID_HEADER.parse().unwrap(),
+            MakeRequestUuid,
+        ))
+}
+
+/// The span every request is handled in, so handler logs carry its ID.
+fn request_span<B>(request: &Request<B>) -> tracing::Span {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .unwrap_or_default();
+    tracing::info_span!(
+        "request",
+        request_id,
+        method = %request.method(),
+        uri = %request.uri(),
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = "/register",
+    request_body = Registration,
+    params(("Idempotency-Key" = Option<String>, Header,
+        description = "Retries with the same key within 10 minutes get the first attempt's response")),
+    responses(
+        (status = 201, description = "Email registered", body = Registration,
+            headers(("Location" = String, description = "The new email resource"))),
+        (status = 409, description = "Email already registered", body = ApiError),
+        (status = 422, description = "Idempotency key reused for another email", body = ApiError)
+    )
+)]
+async fn register(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<Registration>,
+) -> Response {
+    let key = headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|key| key.to_str().ok())
+        .map(str::to_string);
+    let Some(key) = key else {
+        let outcome = insert_email(&state, &payload.email);
+        return registration_response(outcome, payload);
+    };
+
+    // Held for the whole registration so concurrent retries wait for the first
+    let mut keys = state.idempotency_keys();
+    if let Some(replay) = keys.get(&key) {
+        if replay.email != payload.email {
+            let error = ApiError {
+                error: format!(
+                    "{} {} was already used to register another email",
+                    IDEMPOTENCY_KEY_HEADER, key
+                ),
+            };
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(error)).into_response();
+        }
+        tracing::info!(email = %payload.email, key, "Replayed registration");
+        return registration_response(replay.outcome, payload);
+    }
+    let outcome = insert_email(&state, &payload.email);
+    keys.insert(
+        key,
+        Replay {
+            stored_at: Instant::now(),
+            email: payload.email.clone(),
+            outcome,
+        },
+    );
+    registration_response(outcome, payload)
+}
+
+fn insert_email(state: &AppState, email: &str) -> Registered {
+    let mut auth_list = state.emails();
+    if auth_list.contains(email) {
+        tracing::info!(email, "Email already registered");
+        return Registered::AlreadyRegistered;
+    }
+    tracing::info!(email, "Registered email");
+    auth_list.insert(email.to_string());
+    Registered::Created
+}
+
+fn registration_response(outcome: Registered, registration: Registration) -> Response {
+    match outcome {
+        Registered::Created => {
+            let location = email_location(&registration.email);
+            (
+                StatusCode::CREATED,
+                [(header::LOCATION, location)],
+                Json(registration),
+            )
+                .into_response()
+        }
+        Registered::AlreadyRegistered => {
+            let error = ApiError {
+                error: format!("{} is already registered", registration.email),
+            };
+            (StatusCode::CONFLICT, Json(error)).into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/emails/{email}",
+    params(("email" = String, Path, description = "A registered email")),
+    responses(
+        (status = 200, description = "The email is registered", body = Registration),
+        (status = 404, description = "The email is not registered", body = ApiError)
+    )
+)]
+async fn get_email(State(state): State<AppState>, Path(email): Path<String>) -> Response {
+    if state.emails().contains(&email) {
+        return Json(Registration { email }).into_response();
+    }
+    let error = ApiError {
+        error: format!("{} is not registered", email),
+    };
+    (StatusCode::NOT_FOUND, Json(error)).into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/is-authorized",
+    params(Registration),
+    responses((status = 200, description = "Whether the email is authorized", body = bool, content_type = "application/json"))
+)]
+async fn is_authorized(
+    State(state): State<AppState>,
+    Query(params): Query<Registration>,
+) -> Json<bool> {
+    let auth_list = state.emails();
+    let authorized = auth_list.contains(&params.email);
+    tracing::info!(email = %params.email, authorized, "Checked authorization");
+    Json(authorized)
+}
+
+#[utoipa::path(
+    get,
+    path = "/list-emails",
+    responses((status = 200, description = "All registered emails", body = Vec<String>))
+)]
+async fn list_emails(State(state): State<AppState>) -> Json<Vec<String>> {
+    let auth_list = state.emails();
+    Json(auth_list.iter().cloned().collect())
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses((status = 200, description = "The service is up", body = Health))
+)]
+async fn health(State(state): State<AppState>) -> Json<Health> {
+    Json(Health {
+        status: "ok",
+        authorized_emails: state.emails().len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    fn registration(email: &str) -> Request<Body> {
+        Request::post("/register")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::json!({ "email": email }).to_string(),
+            ))
+            .unwrap()
+    }
+
+    fn keyed_registration(email: &str, key: &str) -> Request<Body> {
+        let mut request = registration(email);
+        request
+            .headers_mut()
+            .insert(IDEMPOTENCY_KEY_HEADER, key.parse().unwrap());
+        request
+    }
+
+    async fn send(
+        app: &Router,
+        request: Request<Body>,
+    ) -> (StatusCode, HeaderMap, serde_json::Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, headers, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn openapi_document_lists_schemas_and_paths() {
+        let document = ApiDoc::openapi().to_json().unwrap();
+        let document: serde_json::Value = serde_json::from_str(&document).unwrap();
+        let schemas = &document["components"]["schemas"];
+        assert!(schemas.get("StockPrice").is_some());
+        assert!(schemas.get("Registration").is_some());
+        assert!(document["paths"].get("/register").is_some());
+    }
+
+    #[tokio::test]
+    async fn openapi_json_is_served() {
+        let request = Request::get("/openapi.json").body(Body::empty()).unwrap();
+        let response = router(AppState::new()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn register_creates_the_email_resource() {
+        let app = router(AppState::new());
+        let (status, headers, body) = send(&app, registration("user@example.com")).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(headers[header::LOCATION], "/emails/user@example.com");
+        assert_eq!(body, serde_json::json!({ "email": "user@example.com" }));
+    }
+
+    #[tokio::test]
+    async fn registering_twice_conflicts() {
+        let app = router(AppState::new());
+        send(&app, registration("user@example.com")).await;
+        let (status, headers, body) = send(&app, registration("user@example.com")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(headers.get(header::LOCATION).is_none());
+        assert_eq!(
+            body,
+            serde_json::json!({ "error": "user@example.com is already registered" })
+        );
+    }
+
+    #[tokio::test]
+    async fn replayed_key_returns_the_first_response() {
+        let state = AppState::new();
+        let app = router(state.clone());
+        let first = send(&app, keyed_registration("user@example.com", "k1")).await;
+        let replayed = send(&app, keyed_registration("user@example.com", "k1")).await;
+        assert_eq!(replayed.0, StatusCode::CREATED);
+        assert_eq!(replayed.1[header::LOCATION], first.1[header::LOCATION]);
+        assert_eq!(replayed.2, first.2);
+        assert_eq!(state.emails().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn key_reused_for_another_email_is_rejected() {
+        let state = AppState::new();
+        let app = router(state.clone());
+        send(&app, keyed_registration("user@example.com", "k1")).await;
+        let (status, _, _) = send(&app, keyed_registration("other@example.com", "k1")).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(!state.emails().contains("other@example.com"));
+    }
+
+    #[tokio::test]
+    async fn keys_expire_after_their_ttl() {
+        let app = router(AppState::new().with_idempotency_ttl(Duration::from_millis(20)));
+        send(&app, keyed_registration("user@example.com", "k1")).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        // No longer replayed, so the email is found already registered
+        let (status, _, _) = send(&app, keyed_registration("user@example.com", "k1")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,210 @@
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::{fmt, io, io::Read};
+
+use crate::types::{PayloadError, StockPrice};
+use crate::wire::WireFormat;
+
+/// Header naming the payload's compression; only `gzip` is understood.
+pub const CONTENT_ENCODING_HEADER: &str = "Content-Encoding";
+
+/// Header carrying how many ticks a batch holds, so a corrupted batch can
+/// still report how many were lost.
+pub const BATCH_COUNT_HEADER: &str = "Batch-Count";
+
+/// Token appended to the tick subject for batches, e.g. `stock_prices.batch`.
+pub const BATCH_SUBJECT_SUFFIX: &str = "batch";
+
+/// The subject batches for `subject` are published on.
+pub fn batch_subject(subject: &str) -> String {
+    format!("{}.{}", subject, BATCH_SUBJECT_SUFFIX)
+}
+
+/// Whether `subject` is a batch subject, i.e. its last token is `batch`.
+pub fn is_batch_subject(subject: &str) -> bool {
+    subject
+        .strip_suffix(BATCH_SUBJECT_SUFFIX)
+        .is_some_and(|rest| rest.ends_with('.'))
+}
+
+/// Several ticks published as one message, as
+/// `{"ticks": [{"symbol": ..., "price": ..., "timestamp": ...}, ...]}` in the
+/// configured wire format.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TickBatch {
+    pub ticks: Vec<StockPrice>,
+}
+
+#[derive(Debug)]
+pub enum BatchError {
+    /// A `Content-Encoding: gzip` payload that would not inflate.
+    Decompress(io::Error),
+    /// A batch whose ticks could not be decoded; every one of them is lost,
+    /// and `lost` says how many when a `Batch-Count` header declared it.
+    Corrupt {
+        lost: Option<usize>,
+        source: PayloadError,
+    },
+    /// A batch holding a different number of ticks than its header declared.
+    CountMismatch { declared: usize, found: usize },
+}
+
+impl BatchError {
+    /// Ticks dropped with the batch, when known.
+    pub fn lost(&self) -> Option<usize> {
+        match self {
+            BatchError::Decompress(_) => None,
+            BatchError::Corrupt { lost, .. } => *lost,
+            BatchError::CountMismatch { declared, .. } => Some(*declared),
+        }
+    }
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchError::Decompress(e) => write!(f, "cannot decompress gzip payload: {}", e),
+            BatchError::Corrupt {
+                lost: Some(lost),
+                source,
+            } => write!(f, "corrupted batch, lost {} ticks: {}", lost, source),
+            BatchError::Corrupt { lost: None, source } => {
+                write!(f, "corrupted batch: {}", source)
+            }
+            BatchError::CountMismatch { declared, found } => write!(
+                f,
+                "corrupted batch, lost {} ticks: header declared {} but payload held {}",
+                declared, declared, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BatchError::Decompress(e) => Some(e),
+            BatchError::Corrupt { source, .. } => Some(source),
+            BatchError::CountMismatch { .. } => None,
+        }
+    }
+}
+
+/// Inflates a gzip payload.
+pub fn gunzip(payload: &[u8]) -> Result<Vec<u8>, BatchError> {
+    let mut inflated = Vec::new();
+    GzDecoder::new(payload)
+        .read_to_end(&mut inflated)
+        .map_err(BatchError::Decompress)?;
+    Ok(inflated)
+}
+
+/// Decodes a batch envelope.
+///
+/// Only call this for messages known to be batches: those on a batch
+/// subject or carrying a `Batch-Count` header, so single ticks are parsed
+/// once rather than tried as a batch first. With a declared count the batch
+/// must hold exactly that many ticks. Ticks are returned unvalidated so
+/// each can be rejected on its own.
+pub fn decode_batch(
+    format: WireFormat,
+    payload: &[u8],
+    declared: Option<usize>,
+) -> Result<Vec<StockPrice>, BatchError> {
+    let batch = format
+        .decode_as::<TickBatch>(payload)
+        .map_err(|source| BatchError::Corrupt {
+            lost: declared,
+            source,
+        })?;
+    if let Some(declared) = declared
+        && declared != batch.ticks.len()
+    {
+        return Err(BatchError::CountMismatch {
+            declared,
+            found: batch.ticks.len(),
+        });
+    }
+    Ok(batch.ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch_of(count: usize) -> TickBatch {
+        TickBatch {
+            ticks: (0..count)
+                .map(|i| StockPrice {
+                    symbol: format!("SYM{}", i),
+                    price: i as f64,
+                    seq: i as u64 + 1,
+                    ..StockPrice::default()
+                })
+                .collect(),
+        }
+    }
+
+    fn decode(count: usize, declared: Option<usize>) -> Result<Vec<StockPrice>, BatchError> {
+        let payload = WireFormat::Json.encode(&batch_of(count)).unwrap();
+        decode_batch(WireFormat::Json, &payload, declared)
+    }
+
+    #[test]
+    fn batches_of_any_size_decode() {
+        for count in [0, 1, 1000] {
+            let ticks = decode(count, Some(count)).unwrap();
+            assert_eq!(ticks.len(), count);
+            if let Some(last) = ticks.last() {
+                assert_eq!(last.symbol, format!("SYM{}", count - 1));
+                assert_eq!(last.seq, count as u64);
+            }
+            assert_eq!(decode(count, None).unwrap().len(), count);
+        }
+    }
+
+    #[test]
+    fn count_mismatch_is_rejected() {
+        match decode(3, Some(5)) {
+            Err(
+                error @ BatchError::CountMismatch {
+                    declared: 5,
+                    found: 3,
+                },
+            ) => {
+                assert_eq!(error.lost(), Some(5));
+            }
+            other => panic!("expected a count mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn corrupt_batch_reports_declared_loss() {
+        let declared = decode_batch(WireFormat::Json, b"{\"ticks\": [", Some(4)).unwrap_err();
+        assert!(matches!(
+            declared,
+            BatchError::Corrupt { lost: Some(4), .. }
+        ));
+        assert_eq!(declared.lost(), Some(4));
+
+        let undeclared = decode_batch(WireFormat::Json, b"not a batch", None).unwrap_err();
+        assert_eq!(undeclared.lost(), None);
+    }
+
+    #[test]
+    fn single_tick_is_not_a_batch() {
+        let tick = WireFormat::Json
+            .encode(&batch_of(1).ticks.remove(0))
+            .unwrap();
+        assert!(decode_batch(WireFormat::Json, &tick, None).is_err());
+    }
+
+    #[test]
+    fn recognizes_batch_subjects() {
+        assert!(is_batch_subject(&batch_subject("stock_prices")));
+        assert!(is_batch_subject("prod.stock_prices.batch"));
+        assert!(!is_batch_subject("stock_prices"));
+        assert!(!is_batch_subject("batch"));
+        assert!(!is_batch_subject("stock_prices.nobatch"));
+    }
+}
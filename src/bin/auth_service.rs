@@ -1,66 +1,52 @@
-use axum::extract::Query;
-use axum::{
-    Router,
-    extract::Json,
-    extract::State,
-    routing::{get, post},
-};
-use serde::{Deserialize, Serialize};
-use std::{
-    collections::HashSet,
-    net::SocketAddr,
-    sync::{Arc, Mutex},
-};
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-struct Registration {
-    email: String,
+use clap::Parser;
+use std::path::PathBuf;
+use tracing_subscriber::EnvFilter;
+
+use stock_ticker::auth_service::{self, AppState};
+use stock_ticker::config::{self, Config};
+
+#[derive(Parser, Debug)]
+#[command(about = "Register and check authorized emails")]
+struct Args {
+    /// TOML config file; environment variables override its values
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
 }
 
-#[derive(Clone)]
-struct AppState {
-    authorized_emails: Arc<Mutex<HashSet<String>>>,
+/// Set to `json` for one JSON object per log line; anything else logs text.
+const LOG_FORMAT_ENV: &str = "LOG_FORMAT";
+
+/// Logs to stdout at the level in `RUST_LOG` (default `info`).
+fn init_logging() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let logger = tracing_subscriber::fmt().with_env_filter(filter);
+    if std::env::var(LOG_FORMAT_ENV).is_ok_and(|format| format.eq_ignore_ascii_case("json")) {
+        logger.json().with_current_span(true).init();
+    } else {
+        logger.init();
+    }
 }
 
 #[tokio::main]
 async fn main() {
-    let state = AppState {
-        authorized_emails: Arc::new(Mutex::new(HashSet::new())),
+    config::load_dotenv();
+    let args = Args::parse();
+    init_logging();
+    let config = match Config::load(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("{}", e);
+            std::process::exit(1);
+        }
     };
 
-    let app = Router::new()
-        .route("/register", post(register))
-        .route("/is-authorized", get(is_authorized))
-        .route("/list-emails", get(list_emails))
-        .with_state(state);
+    let app = auth_service::router(AppState::new());
 
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3001));
-    println!("🔐 Auth service running on http://{}", addr);
+    let addr = config.auth_service.bind;
+    tracing::info!("🔐 Auth service running on http://{}", addr);
 
     // Updated server binding code for axum 0.8.1
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    println!("Listening on {}", addr);
+    tracing::info!("Listening on {}", addr);
     axum::serve(listener, app).await.unwrap();
 }
-
-async fn register(
-    State(state): State<AppState>,
-    Json(payload): Json<Registration>,
-) -> &'static str {
-    let mut auth_list = state.authorized_emails.lock().unwrap();
-    auth_list.insert(payload.email);
-    "✅ Registered"
-}
-
-async fn is_authorized(
-    State(state): State<AppState>,
-    Query(params): Query<Registration>,
-) -> Json<bool> {
-    let auth_list = state.authorized_emails.lock().unwrap();
-    Json(auth_list.contains(&params.email))
-}
-
-async fn list_emails(State(state): State<AppState>) -> Json<Vec<String>> {
-    let auth_list = state.authorized_emails.lock().unwrap();
-    Json(auth_list.iter().cloned().collect())
-}
@@ -0,0 +1,1293 @@
+use async_nats::jetstream::{AckKind, message::Acker};
+use clap::{Parser, ValueEnum};
+use futures::StreamExt;
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant, interval, sleep};
+
+use stock_ticker::alerts::{Alert, AlertEngine, AlertParseError, AlertRule, Comparison};
+use stock_ticker::auth_client::{
+    AuthClient, AuthError, REVOCATION_SUBJECT, load_credentials, revoked_email,
+};
+use stock_ticker::board::SymbolBoard;
+use stock_ticker::config::{self, Config};
+use stock_ticker::counters::{MessageCounters, RunSummary, TOP_SYMBOLS, ThroughputReporter};
+use stock_ticker::dead_letter::DeadLetterFile;
+use stock_ticker::filter::TickFilter;
+use stock_ticker::format::{OutputFormat, Projection, TickPrinter, color_enabled};
+use stock_ticker::forward::{DEFAULT_QUEUE_CAPACITY, ForwardConfig, ForwardHeader, Forwarder};
+use stock_ticker::gaps::GapDetector;
+use stock_ticker::jetstream::ReplayFrom;
+use stock_ticker::latency::LatencyHistogram;
+use stock_ticker::nats;
+use stock_ticker::quote::request_quote;
+use stock_ticker::recorder::{RecordFormat, Recorder, RecorderConfig, SYNC_INTERVAL};
+use stock_ticker::shutdown::Shutdown;
+use stock_ticker::stats::{StatsWindow, SymbolStats};
+use stock_ticker::store::{StoreConfig, TickStore};
+use stock_ticker::subscriber::{
+    Decoded, Delivery, DurableConfig, StockSubscriber, SubscribeError, SubscriberOptions,
+    inflated_payload,
+};
+use stock_ticker::types::PayloadError;
+use stock_ticker::wire::{WIRE_FORMAT_ENV, WireFormat};
+use stock_ticker::ws::{self, DEFAULT_WS_URL};
+
+mod metrics;
+mod tui;
+
+use metrics::{ConsumerMetrics, MetricsState};
+
+#[derive(Parser, Debug)]
+#[command(about = "Subscribe to live stock prices")]
+struct Args {
+    /// Email address registered with the auth service (deprecated; use
+    /// --credentials)
+    email: Option<String>,
+
+    /// File holding the email address; preferred over the positional email
+    #[arg(long, value_name = "FILE")]
+    credentials: Option<PathBuf>,
+
+    /// TOML config file; environment variables override its values and
+    /// flags override both
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Base URL of the auth service [default: http://localhost:3001]
+    #[arg(long)]
+    auth_url: Option<String>,
+
+    /// Skip the authorization check (local development only)
+    #[arg(long)]
+    skip_auth: bool,
+
+    /// Re-check authorization every N seconds (0 disables) [default: 60]
+    #[arg(long)]
+    reauth_interval: Option<u64>,
+
+    /// Append every received payload to this file
+    #[arg(long)]
+    record: Option<PathBuf>,
+
+    /// Rotate the record file once it grows beyond this many megabytes
+    #[arg(long, default_value_t = 100)]
+    record_max_mb: u64,
+
+    /// Number of rotated record files to keep
+    #[arg(long, default_value_t = 5)]
+    record_keep: usize,
+
+    /// Format of the record file
+    #[arg(long, value_enum, default_value_t = RecordFormat::Jsonl)]
+    record_format: RecordFormat,
+
+    /// Append messages that fail to parse, with the error, to this JSON Lines file
+    #[arg(long)]
+    dead_letter: Option<PathBuf>,
+
+    /// Insert every valid tick into this SQLite database
+    #[arg(long, visible_alias = "db")]
+    sqlite: Option<PathBuf>,
+
+    /// Rows per SQLite transaction
+    #[arg(long, requires = "sqlite", default_value_t = stock_ticker::store::DEFAULT_BATCH_SIZE)]
+    sqlite_batch: usize,
+
+    /// Flush pending SQLite rows at least this often, in milliseconds
+    #[arg(
+        long,
+        requires = "sqlite",
+        default_value_t = stock_ticker::store::DEFAULT_FLUSH_INTERVAL.as_millis() as u64,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    sqlite_flush_ms: u64,
+
+    /// Enable WAL mode so other processes can query the database while it is written
+    #[arg(long, requires = "sqlite")]
+    sqlite_wal: bool,
+
+    /// Only keep ticks for these symbols (comma-separated); takes precedence
+    /// over --exclude-symbols
+    #[arg(long, value_delimiter = ',', value_name = "SYMBOLS")]
+    symbols: Vec<String>,
+
+    /// Drop ticks for these symbols (comma-separated), even under wildcard
+    /// subscriptions
+    #[arg(long, value_delimiter = ',', value_name = "SYMBOLS")]
+    exclude_symbols: Vec<String>,
+
+    /// Drop ticks priced below this
+    #[arg(long)]
+    min_price: Option<f64>,
+
+    /// Drop ticks priced above this
+    #[arg(long)]
+    max_price: Option<f64>,
+
+    /// Serve Prometheus metrics on this port at /metrics
+    #[arg(long, value_name = "PORT")]
+    metrics_port: Option<u16>,
+
+    /// Alert rule such as "TSLA>300", "AAPL<=150" or "MSFT crosses 400" (repeatable)
+    #[arg(long = "alert")]
+    alerts: Vec<AlertRule>,
+
+    /// Alert when a price rises above this: "300" for every symbol or
+    /// "TSLA=300" for one (repeatable)
+    #[arg(long, value_name = "[SYMBOL=]PRICE", value_parser = parse_alert_above)]
+    alert_above: Vec<AlertRule>,
+
+    /// Alert when a price falls below this: "150" for every symbol or
+    /// "AAPL=150" for one (repeatable)
+    #[arg(long, value_name = "[SYMBOL=]PRICE", value_parser = parse_alert_below)]
+    alert_below: Vec<AlertRule>,
+
+    /// Ring the terminal bell when an alert fires
+    #[arg(long)]
+    alert_bell: bool,
+
+    /// POST each fired alert as JSON to this URL
+    #[arg(long)]
+    alert_webhook: Option<String>,
+
+    /// POST every valid tick as JSON to this URL from a background task
+    #[arg(long)]
+    forward_webhook: Option<String>,
+
+    /// Ticks per forwarded POST; above 1 the body is a JSON array
+    #[arg(
+        long,
+        requires = "forward_webhook",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    forward_batch_size: u64,
+
+    /// Ticks buffered for forwarding before new ones are dropped
+    #[arg(long, requires = "forward_webhook", default_value_t = DEFAULT_QUEUE_CAPACITY)]
+    forward_queue: usize,
+
+    /// Extra header on forwarded requests, e.g. "Authorization: Bearer x" (repeatable)
+    #[arg(long, requires = "forward_webhook")]
+    forward_header: Vec<ForwardHeader>,
+
+    /// Measure end-to-end delivery latency from each message's publish timestamp
+    #[arg(long)]
+    latency: bool,
+
+    /// Disable colored output (also honored: the NO_COLOR environment variable)
+    #[arg(long)]
+    no_color: bool,
+
+    /// How each tick is printed; with anything but pretty, status messages
+    /// go to stderr so stdout stays machine-readable
+    #[arg(long, value_enum, default_value_t = OutputFormat::Pretty)]
+    format: OutputFormat,
+
+    /// Comma-separated fields for jsonl/csv/table output, in order, e.g.
+    /// "symbol,price,publisher_id"
+    #[arg(long, value_name = "FIELDS")]
+    fields: Option<Projection>,
+
+    /// Write ticks to this file instead of stdout, in --format; flushed
+    /// and closed on exit
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Show a live dashboard instead of printing each message
+    #[arg(long)]
+    tui: bool,
+
+    /// Benchmark: consume as fast as possible with no per-message output, then
+    /// report throughput and latency percentiles (runs for --max-duration, default 30s)
+    #[arg(long, conflicts_with = "tui")]
+    bench: bool,
+
+    /// Exit after this many ticks pass the symbol and price filters; bars,
+    /// market status and dropped ticks do not count
+    #[arg(long, visible_alias = "count")]
+    max_messages: Option<u64>,
+
+    /// Exit after running for this long (e.g. "30s", "5m")
+    #[arg(long, value_parser = humantime::parse_duration)]
+    max_duration: Option<Duration>,
+
+    /// Exit after running for this many seconds; --max-duration in plain seconds
+    #[arg(long, value_name = "N", conflicts_with = "max_duration")]
+    duration_secs: Option<u64>,
+
+    /// Write the JSON run summary to this file instead of stderr
+    #[arg(long)]
+    summary_file: Option<PathBuf>,
+
+    /// Number of recent Nats-Msg-Id values remembered for deduplication
+    /// (0 disables, at most 10 million)
+    #[arg(
+        long,
+        default_value_t = 10_000,
+        value_parser = clap::value_parser!(u64).range(..=MAX_DEDUP_SIZE)
+    )]
+    dedup_size: u64,
+
+    /// How long a message ID is remembered for deduplication (e.g. "2m")
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "2m")]
+    dedup_ttl: Duration,
+
+    /// Print the latest price of this symbol from the quote service and exit
+    #[arg(long)]
+    quote: Option<String>,
+
+    /// Encoding of tick payloads; must match the publisher
+    #[arg(long, value_enum, env = WIRE_FORMAT_ENV, default_value_t = WireFormat::Json)]
+    wire_format: WireFormat,
+
+    /// Where ticks are received from
+    #[arg(long, value_enum, default_value_t = Transport::Nats)]
+    transport: Transport,
+
+    /// Aggregator WebSocket stream used with --transport ws
+    #[arg(long, default_value = DEFAULT_WS_URL)]
+    ws_url: String,
+
+    /// Subscribe to this subject pattern instead of the tick subject (repeatable),
+    /// e.g. "stock_prices.>", "stock_bars.>" and "market_status"
+    #[arg(long, conflicts_with = "jetstream")]
+    subscribe: Vec<String>,
+
+    /// Print messages on subjects that carry no known payload type
+    #[arg(long)]
+    show_unknown: bool,
+
+    /// Join this queue group so instances share the message load instead of each receiving every message
+    #[arg(long, conflicts_with = "jetstream")]
+    queue_group: Option<String>,
+
+    /// Consume through a durable JetStream consumer instead of a plain subscription
+    #[arg(long, requires = "durable")]
+    jetstream: bool,
+
+    /// Name of the durable JetStream consumer; the server remembers its ack floor
+    #[arg(long, requires = "jetstream")]
+    durable: Option<String>,
+
+    /// JetStream stream to bind to (defaults to the stream capturing the subject)
+    #[arg(long, requires = "jetstream")]
+    stream: Option<String>,
+
+    /// Start a new durable consumer at this stream sequence or RFC 3339 time
+    #[arg(long, requires = "jetstream")]
+    replay_from: Option<ReplayFrom>,
+
+    /// Keep per-symbol rolling statistics over the last N ticks or a duration (e.g. "100", "5m")
+    #[arg(long)]
+    rolling_stats: Option<StatsWindow>,
+
+    /// Print a throughput summary line every N seconds
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    stats_interval: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Transport {
+    /// Subscribe to the NATS server
+    Nats,
+    /// Read the aggregator's WebSocket stream, for hosts that cannot reach NATS
+    Ws,
+}
+
+/// Exit code when the auth service denies access.
+const EXIT_DENIED: u8 = 2;
+
+/// Exit code when the auth service cannot be reached after retries.
+const EXIT_AUTH_UNREACHABLE: u8 = 3;
+
+/// Exit code when authorization is revoked while consuming.
+const EXIT_REVOKED: u8 = 4;
+
+/// How long `--bench` runs when `--max-duration` is not given.
+const BENCH_DURATION: Duration = Duration::from_secs(30);
+
+/// How often the latency histogram is reported while `--latency` is enabled.
+const LATENCY_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long a graceful shutdown waits for in-flight messages after draining
+/// the NATS subscriptions.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest accepted `--dedup-size`.
+const MAX_DEDUP_SIZE: u64 = 10_000_000;
+
+/// Set when stdout carries machine-readable ticks, so status messages move
+/// to stderr.
+static STATUS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Prints a status message: stdout normally, stderr when `--format` makes
+/// stdout machine-readable.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if STATUS_TO_STDERR.load(Ordering::Relaxed) {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Reports warnings from background work: on stderr, or while quiet as the
+/// dashboard's warning line, since stderr would draw over the TUI.
+#[derive(Clone)]
+struct Warnings {
+    quiet: bool,
+    dashboard: Arc<Mutex<tui::Dashboard>>,
+}
+
+impl Warnings {
+    fn warn(&self, line: String) {
+        if self.quiet {
+            let mut dashboard = self.dashboard.lock().unwrap_or_else(|e| e.into_inner());
+            dashboard.last_warning = Some(line);
+            dashboard.warnings += 1;
+        } else {
+            eprintln!("{}", line);
+        }
+    }
+}
+
+fn parse_alert_above(spec: &str) -> Result<AlertRule, AlertParseError> {
+    AlertRule::threshold(spec, Comparison::Above)
+}
+
+fn parse_alert_below(spec: &str) -> Result<AlertRule, AlertParseError> {
+    AlertRule::threshold(spec, Comparison::Below)
+}
+
+fn raise_alert(
+    alert: &Alert,
+    dashboard: Option<&mut tui::Dashboard>,
+    bell: bool,
+    webhook: Option<&String>,
+    http: &reqwest::Client,
+    metrics: Option<&Arc<ConsumerMetrics>>,
+    warnings: &Warnings,
+) {
+    let line = format!(
+        "ALERT [{}] {} is now {:.2} (at {})",
+        alert.rule, alert.symbol, alert.price, alert.timestamp
+    );
+    match dashboard {
+        Some(dashboard) => dashboard.last_alert = Some(line),
+        None => eprintln!("🚨 {}", line),
+    }
+    if bell {
+        print!("\x07");
+    }
+    if let Some(url) = webhook {
+        let request = http.post(url).json(alert);
+        let metrics = metrics.cloned();
+        let warnings = warnings.clone();
+        tokio::spawn(async move {
+            if let Err(e) = request.send().await.and_then(|r| r.error_for_status()) {
+                warnings.warn(format!("❌ Failed to deliver alert webhook: {}", e));
+                if let Some(metrics) = metrics {
+                    metrics
+                        .alert_webhook_failures
+                        .fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        });
+    }
+}
+
+async fn settle(acker: Option<&Acker>, kind: AckKind, warnings: &Warnings) {
+    if let Some(acker) = acker
+        && let Err(e) = acker.ack_with(kind).await
+    {
+        warnings.warn(format!("⚠️  Failed to acknowledge message: {}", e));
+    }
+}
+
+/// Signals each time rolling statistics are requested: `s` followed by Enter
+/// on stdin, or SIGUSR1 on Unix.
+fn stats_requests(warnings: &Warnings) -> mpsc::Receiver<()> {
+    let (tx, rx) = mpsc::channel(1);
+
+    // A plain thread, since a blocked stdin read would hold up runtime shutdown
+    let stdin_tx = tx.clone();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            match line {
+                Ok(line) if line.trim() == "s" => {
+                    let _ = stdin_tx.try_send(());
+                }
+                Ok(_) => {}
+                Err(_) => return,
+            }
+        }
+    });
+
+    #[cfg(not(unix))]
+    let _ = warnings;
+    #[cfg(unix)]
+    let warnings = warnings.clone();
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{SignalKind, signal};
+        let mut usr1 = match signal(SignalKind::user_defined1()) {
+            Ok(usr1) => usr1,
+            Err(e) => {
+                warnings.warn(format!("⚠️  Failed to listen for SIGUSR1: {}", e));
+                return;
+            }
+        };
+        while usr1.recv().await.is_some() {
+            let _ = tx.try_send(());
+        }
+    });
+
+    rx
+}
+
+fn print_bench_report(
+    summary: &RunSummary,
+    latency: Option<&LatencyHistogram>,
+    client: Option<&async_nats::Client>,
+) {
+    status!(
+        "🏁 Bench: {} messages in {:.1}s ({:.0} msg/s)",
+        summary.received,
+        summary.duration_secs,
+        summary.msgs_per_sec
+    );
+    if let Some(latency) = latency
+        && latency.count() > 0
+    {
+        status!(
+            "⏱️  Latency: p50={:.3}ms p99={:.3}ms p99.9={:.3}ms max={:.3}ms",
+            latency.percentile_ms(50.0),
+            latency.percentile_ms(99.0),
+            latency.percentile_ms(99.9),
+            latency.max_ms()
+        );
+    }
+    if let Some(client) = client {
+        // The client counts every message it reads off the socket, including
+        // ones it discards because the subscription buffer was full
+        let in_messages = client.statistics().in_messages.load(Ordering::Relaxed);
+        status!(
+            "🚫 Not delivered by the NATS client (slow consumer or still buffered): {}",
+            in_messages.saturating_sub(summary.received)
+        );
+    }
+}
+
+/// Suffix for the stats lines once `--record` has lost records.
+fn recorder_state(recorder: Option<&Recorder>) -> String {
+    match recorder {
+        Some(recorder) if recorder.is_degraded() => {
+            format!(" | recorder degraded, {} dropped", recorder.dropped())
+        }
+        Some(recorder) if recorder.dropped() > 0 => {
+            format!(" | recorder dropped {}", recorder.dropped())
+        }
+        _ => String::new(),
+    }
+}
+
+fn print_rolling_stats(stats: &mut SymbolStats) {
+    status!("📈 Rolling stats ({}):", stats.window());
+    for (symbol, window) in stats.snapshot() {
+        status!("   {:<6} {}", symbol, window);
+    }
+}
+
+/// Ticks received and the last price per symbol, sorted by symbol.
+fn print_symbol_summary(board: &SymbolBoard) {
+    if board.is_empty() {
+        return;
+    }
+    let mut symbols: Vec<_> = board.iter().collect();
+    symbols.sort_by(|a, b| a.0.cmp(b.0));
+    status!("📋 Received per symbol:");
+    for (symbol, window) in symbols {
+        status!(
+            "   {:<6} {:>8} ticks, last {:.2}",
+            symbol,
+            window.count,
+            window.last
+        );
+    }
+}
+
+/// Watches for loss of authorization, either through a periodic re-check
+/// against the auth service or, when connected to NATS, an announcement on
+/// `auth.revoked`, and sends the reason on the returned channel.
+///
+/// Auth service outages during a re-check are logged and ignored; only an
+/// explicit "not authorized" answer revokes access.
+fn watch_authorization(
+    client: Option<async_nats::Client>,
+    auth: AuthClient,
+    email: String,
+    every: Option<Duration>,
+    shutdown: Shutdown,
+    warnings: Warnings,
+) -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel(1);
+
+    if let Some(every) = every {
+        let tx = tx.clone();
+        let email = email.clone();
+        let shutdown = shutdown.clone();
+        let warnings = warnings.clone();
+        tokio::spawn(async move {
+            let mut timer = interval(every);
+            timer.tick().await;
+            loop {
+                tokio::select! {
+                    _ = timer.tick() => {}
+                    _ = shutdown.triggered() => return,
+                }
+                match auth.is_authorized(&email).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        let _ = tx
+                            .send(format!("auth service no longer authorizes {}", email))
+                            .await;
+                        return;
+                    }
+                    Err(e) => warnings.warn(format!("⚠️  Re-authorization check skipped: {}", e)),
+                }
+            }
+        });
+    }
+
+    let Some(client) = client else {
+        return rx;
+    };
+    tokio::spawn(async move {
+        let mut revocations = match client.subscribe(REVOCATION_SUBJECT).await {
+            Ok(subscriber) => subscriber,
+            Err(e) => {
+                warnings.warn(format!(
+                    "⚠️  Failed to subscribe to '{}': {}",
+                    REVOCATION_SUBJECT, e
+                ));
+                return;
+            }
+        };
+        loop {
+            let message = tokio::select! {
+                Some(message) = revocations.next() => message,
+                _ = shutdown.triggered() => return,
+                else => return,
+            };
+            if revoked_email(&message.payload).is_some_and(|revoked| revoked == email) {
+                let _ = tx
+                    .send(format!(
+                        "{} was revoked via '{}'",
+                        email, REVOCATION_SUBJECT
+                    ))
+                    .await;
+                return;
+            }
+        }
+    });
+
+    rx
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Before parsing, so .env can supply flags read from the environment
+    config::load_dotenv();
+    let args = Args::parse();
+    STATUS_TO_STDERR.store(
+        args.format != OutputFormat::Pretty && args.output.is_none(),
+        Ordering::Relaxed,
+    );
+    if args.fields.is_some() && args.format == OutputFormat::Pretty {
+        eprintln!("❌ --fields needs --format jsonl, csv or table");
+        return ExitCode::FAILURE;
+    }
+    let config = match load_config(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let email = match resolve_email(&args) {
+        Ok(email) => email,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if args.skip_auth {
+        eprintln!(
+            "⚠️⚠️⚠️  --skip-auth is set: NOT checking authorization. Never use this outside local development! ⚠️⚠️⚠️"
+        );
+    } else {
+        match AuthClient::new(&config.consumer.auth_url)
+            .is_authorized(&email)
+            .await
+        {
+            Ok(true) => status!("✅ Access granted. Connecting to NATS..."),
+            Ok(false) => {
+                status!("❌ Access denied for {}", email);
+                return ExitCode::from(EXIT_DENIED);
+            }
+            Err(e @ AuthError::Unreachable { .. }) => {
+                eprintln!("❌ {}", e);
+                return ExitCode::from(EXIT_AUTH_UNREACHABLE);
+            }
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    match run(args, config, email).await {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Loads `--config`, then applies the flags that override it.
+fn load_config(args: &Args) -> Result<Config, Box<dyn std::error::Error>> {
+    let mut config = Config::load(args.config.as_deref())?;
+    if let Some(url) = args.auth_url.as_ref() {
+        config.consumer.auth_url = url.clone();
+    }
+    if !args.symbols.is_empty() {
+        config.consumer.symbols = args.symbols.clone();
+    }
+    if let Some(secs) = args.reauth_interval {
+        config.consumer.reauth_interval = Duration::from_secs(secs);
+    }
+    Ok(config)
+}
+
+/// Picks the email from `--credentials`, falling back to the deprecated
+/// positional argument.
+fn resolve_email(args: &Args) -> Result<String, Box<dyn std::error::Error>> {
+    let Some(path) = args.credentials.as_deref() else {
+        let email = args
+            .email
+            .clone()
+            .ok_or("an email is required: pass --credentials <file>")?;
+        eprintln!(
+            "⚠️  Passing the email as an argument is deprecated; use --credentials <file> instead"
+        );
+        return Ok(email);
+    };
+
+    let credentials = load_credentials(path)?;
+    if let Some(mode) = credentials.exposed_mode {
+        eprintln!(
+            "⚠️  Credentials file {} is readable by group or others (mode {:o}); consider chmod 600",
+            path.display(),
+            mode
+        );
+    }
+    if args.email.is_some() {
+        eprintln!("⚠️  Ignoring the email argument in favour of --credentials");
+    }
+    Ok(credentials.email)
+}
+
+async fn run(
+    args: Args,
+    config: Config,
+    email: String,
+) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    if let Some(symbol) = args.quote.as_deref() {
+        let client = nats::connect(&config.nats.url).await?;
+        let stock = request_quote(&client, symbol).await?;
+        status!(
+            "💬 Quote: {} {:.2} at {}",
+            stock.symbol,
+            stock.price,
+            stock.timestamp
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let quiet = args.tui || args.bench;
+    let dashboard = Arc::new(Mutex::new(tui::Dashboard {
+        stats: args.rolling_stats.map(SymbolStats::new),
+        ..Default::default()
+    }));
+    let warnings = Warnings {
+        quiet,
+        dashboard: dashboard.clone(),
+    };
+    let mut recorder = args.record.as_ref().map(|path| {
+        Recorder::open(RecorderConfig {
+            path: path.clone(),
+            format: args.record_format,
+            max_bytes: args.record_max_mb * 1024 * 1024,
+            keep: args.record_keep,
+            quiet,
+        })
+    });
+
+    let mut dead_letters = args
+        .dead_letter
+        .as_deref()
+        .map(|path| DeadLetterFile::open(path, quiet))
+        .transpose()?;
+
+    let mut store = match args.sqlite.as_ref() {
+        Some(path) => Some(
+            TickStore::open(StoreConfig {
+                path: path.clone(),
+                batch_size: args.sqlite_batch,
+                wal: args.sqlite_wal,
+            })
+            .await?,
+        ),
+        None => None,
+    };
+
+    let mut alerts = AlertEngine::new(
+        args.alerts
+            .iter()
+            .chain(&args.alert_above)
+            .chain(&args.alert_below)
+            .cloned()
+            .collect(),
+    );
+    let http = reqwest::Client::new();
+    let forwarder = args.forward_webhook.as_ref().map(|url| {
+        Forwarder::spawn(
+            ForwardConfig {
+                url: url.clone(),
+                batch_size: args.forward_batch_size as usize,
+                queue_capacity: args.forward_queue,
+                headers: args.forward_header.clone(),
+                quiet,
+            },
+            http.clone(),
+        )
+    });
+
+    let subject = config.nats.subject.clone();
+    let filter = TickFilter::new()
+        .symbols(&config.consumer.symbols)
+        .exclude(&args.exclude_symbols)
+        .price_range(args.min_price, args.max_price);
+    let mut options = SubscriberOptions::new()
+        .tick_subject(&subject)
+        .wire_format(args.wire_format)
+        .filter(filter)
+        .dedup(args.dedup_size as usize, args.dedup_ttl);
+    for pattern in &args.subscribe {
+        options = options.subject(pattern);
+    }
+    if let Some(group) = args.queue_group.as_ref() {
+        options = options.queue_group(group);
+    }
+    if let Some(durable) = args.durable.as_ref() {
+        options = options.durable(DurableConfig {
+            name: durable.clone(),
+            stream: args.stream.clone(),
+            replay_from: args.replay_from,
+        });
+    }
+    let (client, mut deliveries) = match args.transport {
+        Transport::Nats => {
+            // Connect to the NATS server asynchronously
+            let client = nats::connect(&config.nats.url).await?;
+
+            status!("Connected to NATS at {}", config.nats.url);
+
+            let subscriptions = options.subscriptions();
+            let deliveries = StockSubscriber::subscribe(&client, options).await?;
+            match args.durable.as_deref() {
+                Some(durable) => status!(
+                    "Consuming '{}' via durable JetStream consumer '{}'...",
+                    subject,
+                    durable
+                ),
+                None => {
+                    for pattern in subscriptions {
+                        match args.queue_group.as_ref() {
+                            Some(group) => {
+                                status!("Subscribed to '{}' in queue group '{}'...", pattern, group)
+                            }
+                            None => status!("Subscribed to '{}'...", pattern),
+                        }
+                    }
+                }
+            }
+            (Some(client), deliveries)
+        }
+        Transport::Ws => {
+            if args.jetstream || args.queue_group.is_some() {
+                return Err("--jetstream and --queue-group require --transport nats".into());
+            }
+            let frames = ws::connect(&args.ws_url, quiet).await?;
+            status!("Connected to {}", args.ws_url);
+            (None, StockSubscriber::from_frames(frames, options))
+        }
+    };
+
+    let shutdown = Shutdown::on_signals();
+    let mut revoked = if args.skip_auth {
+        mpsc::channel(1).1
+    } else {
+        let every = Some(config.consumer.reauth_interval).filter(|every| !every.is_zero());
+        watch_authorization(
+            client.clone(),
+            AuthClient::new(&config.consumer.auth_url).quiet(quiet),
+            email,
+            every,
+            shutdown.clone(),
+            warnings.clone(),
+        )
+    };
+    let mut exit_code = ExitCode::SUCCESS;
+
+    let mut latency = (args.latency || args.bench).then(LatencyHistogram::new);
+
+    // Flush the record file even when no messages arrive
+    let mut sync_timer = interval(SYNC_INTERVAL);
+    let mut latency_timer = interval(LATENCY_REPORT_INTERVAL);
+    latency_timer.tick().await;
+    let mut store_timer = interval(Duration::from_millis(args.sqlite_flush_ms));
+
+    let color = color_enabled(args.no_color) && args.output.is_none();
+    let mut printer = TickPrinter::new(args.format, args.fields.clone());
+    let mut output = match args.output.as_ref() {
+        Some(path) => Some(BufWriter::new(File::create(path).map_err(|e| {
+            format!("cannot create output file {}: {}", path.display(), e)
+        })?)),
+        None => None,
+    };
+    if let Some(header) = printer.header()
+        && !quiet
+    {
+        match output.as_mut() {
+            Some(output) => writeln!(output, "{}", header)?,
+            None => println!("{}", header),
+        }
+    }
+
+    let counters = Arc::new(MessageCounters::default());
+    let metrics = match args.metrics_port {
+        Some(port) => {
+            let listener = tokio::net::TcpListener::bind(("0.0.0.0", port))
+                .await
+                .map_err(|e| format!("cannot listen for metrics on port {}: {}", port, e))?;
+            let metrics = Arc::new(ConsumerMetrics::default());
+            let state = MetricsState {
+                metrics: metrics.clone(),
+                counters: counters.clone(),
+                nats: client.as_ref().map(|client| client.statistics()),
+                forward: forwarder.as_ref().map(Forwarder::stats_handle),
+            };
+            let warnings = warnings.clone();
+            tokio::spawn(async move {
+                if let Err(e) = metrics::serve(listener, state).await {
+                    warnings.warn(format!("❌ Metrics endpoint failed: {}", e));
+                }
+            });
+            status!("📈 Serving metrics on http://0.0.0.0:{}/metrics", port);
+            Some(metrics)
+        }
+        None => None,
+    };
+    let mut gaps = GapDetector::new();
+    let mut stats_requested = match args.rolling_stats {
+        Some(_) if !quiet => stats_requests(&warnings),
+        _ => mpsc::channel(1).1,
+    };
+    let mut throughput = ThroughputReporter::new();
+    let mut stats_timer = interval(Duration::from_secs(args.stats_interval.unwrap_or(1)));
+    stats_timer.tick().await;
+
+    // Far-off until a shutdown starts draining
+    let drain_deadline = sleep(Duration::from_secs(u32::MAX as u64));
+    tokio::pin!(drain_deadline);
+    let mut draining = false;
+
+    let tui_task = args.tui.then(|| {
+        let dashboard = dashboard.clone();
+        let counters = counters.clone();
+        tokio::task::spawn_blocking(move || tui::run(dashboard, counters))
+    });
+    let tui_done = async {
+        match tui_task {
+            Some(task) => task.await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(tui_done);
+
+    let started = Instant::now();
+    let max_duration = args
+        .max_duration
+        .or(args.duration_secs.map(Duration::from_secs));
+    let deadline = async {
+        match max_duration {
+            Some(duration) => sleep(duration).await,
+            None if args.bench => sleep(BENCH_DURATION).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(deadline);
+
+    // Messages counted towards --max-messages
+    let mut matched: u64 = 0;
+    let limit_reached = |matched: u64| args.max_messages.is_some_and(|max| matched >= max);
+
+    // Loop over incoming messages
+    loop {
+        if limit_reached(matched) {
+            break;
+        }
+
+        let delivery = tokio::select! {
+            delivery = deliveries.next() => match delivery {
+                Some(Ok(delivery)) => delivery,
+                Some(Err(e)) => {
+                    warnings.warn(format!("❌ {}", e));
+                    continue;
+                }
+                None => break,
+            },
+            _ = sync_timer.tick() => {
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.sync();
+                }
+                if let Some(output) = output.as_mut() {
+                    output.flush()?;
+                }
+                continue;
+            }
+            _ = store_timer.tick(), if store.is_some() => {
+                if let Some(store) = store.as_mut() {
+                    store.flush().await;
+                }
+                continue;
+            }
+            _ = latency_timer.tick(), if !quiet => {
+                if let Some(latency) = latency.as_ref() {
+                    status!("⏱️  Latency: {}", latency);
+                }
+                continue;
+            }
+            Some(()) = stats_requested.recv() => {
+                if let Some(stats) = dashboard.lock().unwrap_or_else(|e| e.into_inner()).stats.as_mut() {
+                    print_rolling_stats(stats);
+                }
+                continue;
+            }
+            _ = stats_timer.tick(), if args.stats_interval.is_some() && !quiet => {
+                let board = &dashboard.lock().unwrap_or_else(|e| e.into_inner()).board;
+                let top = board.top_by_count(TOP_SYMBOLS);
+                status!(
+                    "📊 {}{}",
+                    throughput.interval_line(counters.snapshot(), &top),
+                    recorder_state(recorder.as_ref())
+                );
+                continue;
+            }
+            Some(reason) = revoked.recv() => {
+                status!("🚫 Authorization revoked: {}. Unsubscribing...", reason);
+                drop(deliveries);
+                exit_code = ExitCode::from(EXIT_REVOKED);
+                break;
+            }
+            _ = shutdown.triggered(), if !draining => {
+                // Let the subscriptions deliver what is already in flight;
+                // the stream ends once they are drained
+                let Some(client) = client.as_ref() else {
+                    break;
+                };
+                status!("🛑 Shutting down: draining in-flight messages...");
+                if let Err(e) = client.drain().await {
+                    warnings.warn(format!("⚠️  Failed to drain NATS subscriptions: {}", e));
+                    break;
+                }
+                draining = true;
+                drain_deadline.as_mut().reset(Instant::now() + DRAIN_TIMEOUT);
+                continue;
+            }
+            _ = &mut drain_deadline, if draining => {
+                warnings.warn(format!("⚠️  Gave up draining after {:?}", DRAIN_TIMEOUT));
+                break;
+            }
+            _ = &mut deadline => break,
+            result = &mut tui_done => {
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => eprintln!("❌ Dashboard failed: {}", e),
+                    Err(e) => eprintln!("❌ Dashboard task panicked: {}", e),
+                }
+                break;
+            }
+        };
+
+        let Delivery {
+            message,
+            acker,
+            items,
+        } = delivery;
+        let subject: &str = &message.subject;
+
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record(subject, &message.payload);
+        }
+
+        counters.received.fetch_add(1, Ordering::Relaxed);
+
+        // Settled once everything in the message is handled
+        let mut ack = AckKind::Ack;
+        for item in items {
+            let (stock_price, accepted) = match item {
+                Decoded::Tick(tick) => (tick, true),
+                Decoded::Filtered(tick) => (tick, false),
+                Decoded::Bar(bar) => {
+                    if !quiet {
+                        status!(
+                            "🕯️  Bar: {} O {:.2} H {:.2} L {:.2} C {:.2} V {} at {}",
+                            bar.symbol,
+                            bar.open,
+                            bar.high,
+                            bar.low,
+                            bar.close,
+                            bar.volume,
+                            bar.timestamp
+                        );
+                    }
+                    continue;
+                }
+                Decoded::Status(status) => {
+                    if !quiet {
+                        status!("🏛️  Market {} at {}", status.status, status.timestamp);
+                    }
+                    continue;
+                }
+                Decoded::Duplicate => {
+                    counters.duplicates.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                Decoded::Error(SubscribeError::UnknownSubject(subject)) => {
+                    counters.unknown_subjects.fetch_add(1, Ordering::Relaxed);
+                    if args.show_unknown && !quiet {
+                        let payload =
+                            inflated_payload(&message).unwrap_or(Cow::Borrowed(&message.payload));
+                        status!(
+                            "❔ Unknown subject '{}': {}",
+                            subject,
+                            String::from_utf8_lossy(&payload)
+                        );
+                    }
+                    continue;
+                }
+                Decoded::Error(SubscribeError::Payload(PayloadError::Invalid {
+                    symbol,
+                    error,
+                })) => {
+                    counters.rejected.fetch_add(1, Ordering::Relaxed);
+                    if !quiet {
+                        eprintln!("⚠️  Discarding {} tick: {}", symbol, error);
+                    }
+                    continue;
+                }
+                Decoded::Error(e) => {
+                    counters.parse_failures.fetch_add(1, Ordering::Relaxed);
+                    if !quiet {
+                        eprintln!("❌ Failed to parse message: {}", e);
+                    }
+                    if let Some(dead_letters) = dead_letters.as_mut() {
+                        dead_letters.write(subject, &message.payload, &e);
+                    }
+                    // Redelivering an undecodable message would only fail again
+                    ack = AckKind::Term;
+                    continue;
+                }
+            };
+
+            if accepted && limit_reached(matched) {
+                // The rest of the message was never handled: have JetStream
+                // redeliver it instead of acking ticks that were dropped
+                ack = AckKind::Nak(None);
+                break;
+            }
+            if let Some(gap) = gaps.observe(&stock_price.publisher_id, stock_price.seq)
+                && !quiet
+            {
+                eprintln!(
+                    "⚠️  Gap from publisher '{}': missed {} messages (expected seq {}, got {})",
+                    gap.publisher_id,
+                    gap.missed(),
+                    gap.expected,
+                    gap.received
+                );
+            }
+            if let Some(metrics) = metrics.as_ref() {
+                metrics.record(subject, &stock_price);
+            }
+            if !accepted {
+                counters.filtered.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            matched += 1;
+            if let Some(store) = store.as_mut() {
+                store.insert(&stock_price).await;
+            }
+            if let Some(forwarder) = forwarder.as_ref() {
+                forwarder.forward(stock_price.clone());
+            }
+
+            let mut dashboard = dashboard.lock().unwrap_or_else(|e| e.into_inner());
+            let previous = dashboard.board.update(&stock_price);
+            if let Some(stats) = dashboard.stats.as_mut() {
+                stats.update(&stock_price);
+            }
+            if !quiet {
+                let line = printer.line(&stock_price, previous, color);
+                match output.as_mut() {
+                    Some(output) => writeln!(output, "{}", line)?,
+                    None => println!("{}", line),
+                }
+            }
+            if let Some(latency) = latency.as_mut()
+                && !latency.record_since(&stock_price.timestamp)
+                && !quiet
+            {
+                eprintln!("❌ Unparseable timestamp: {}", stock_price.timestamp);
+            }
+            for alert in alerts.evaluate(&stock_price) {
+                raise_alert(
+                    &alert,
+                    quiet.then_some(&mut *dashboard),
+                    args.alert_bell,
+                    args.alert_webhook.as_ref(),
+                    &http,
+                    metrics.as_ref(),
+                    &warnings,
+                );
+            }
+        }
+        settle(acker.as_ref(), ack, &warnings).await;
+    }
+
+    if let Some(recorder) = recorder.as_mut() {
+        recorder.sync();
+    }
+    if let Some(mut output) = output.take() {
+        output.flush()?;
+        if let Some(path) = args.output.as_ref() {
+            status!("📝 Wrote ticks to {}", path.display());
+        }
+    }
+
+    if shutdown.is_triggered() {
+        print_symbol_summary(&dashboard.lock().unwrap_or_else(|e| e.into_inner()).board);
+    }
+
+    if args.stats_interval.is_some() || shutdown.is_triggered() {
+        let board = &dashboard.lock().unwrap_or_else(|e| e.into_inner()).board;
+        let top = board.top_by_count(TOP_SYMBOLS);
+        status!(
+            "📊 Totals: {}{}",
+            throughput.total_line(counters.snapshot(), &top),
+            recorder_state(recorder.as_ref())
+        );
+    }
+
+    if args.bench {
+        print_bench_report(
+            &RunSummary::new(counters.snapshot(), started.elapsed()),
+            latency.as_ref(),
+            client.as_ref(),
+        );
+    } else if let Some(latency) = latency.as_ref() {
+        status!("⏱️  Final latency: {}", latency);
+    }
+
+    if let Some(stats) = dashboard
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .stats
+        .as_mut()
+    {
+        print_rolling_stats(stats);
+    }
+
+    if let Some(dead_letters) = dead_letters.as_ref()
+        && dead_letters.written() > 0
+    {
+        status!("☠️  Wrote {} dead letters", dead_letters.written());
+    }
+    if let Some(dead_letters) = dead_letters.as_ref()
+        && dead_letters.failed() > 0
+    {
+        status!("⚠️  Failed to write {} dead letters", dead_letters.failed());
+    }
+    {
+        let dashboard = dashboard.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(warning) = dashboard.last_warning.as_deref() {
+            status!("{} ({} warnings)", warning, dashboard.warnings);
+        }
+    }
+
+    if let Some(recorder) = recorder.as_ref()
+        && recorder.dropped() > 0
+    {
+        status!(
+            "📼 Recorder dropped {} records{}",
+            recorder.dropped(),
+            if recorder.is_degraded() {
+                " and is still degraded"
+            } else {
+                ""
+            }
+        );
+    }
+
+    if let Some(forwarder) = forwarder {
+        status!("🔁 Webhook forwarding: {}", forwarder.finish().await);
+    }
+
+    if let Some(mut store) = store {
+        store.flush().await;
+        status!(
+            "💾 Wrote {} rows to SQLite ({} failed)",
+            store.written(),
+            store.failed()
+        );
+        store.close().await;
+    }
+
+    if args.max_messages.is_some() || max_duration.is_some() || args.summary_file.is_some() {
+        let summary = RunSummary::new(counters.snapshot(), started.elapsed());
+        let json = serde_json::to_string(&summary)?;
+        match args.summary_file.as_ref() {
+            Some(path) => std::fs::write(path, json + "\n")?,
+            None => eprintln!("{}", json),
+        }
+    }
+
+    Ok(exit_code)
+}
@@ -0,0 +1,162 @@
+use axum::{Router, extract::State, http::header, response::IntoResponse, routing::get};
+use chrono::{DateTime, Utc};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use stock_ticker::counters::MessageCounters;
+use stock_ticker::forward::ForwardStatsHandle;
+use stock_ticker::metrics::{
+    ALERT_WEBHOOK_FAILURES_TOTAL, DUPLICATES_DROPPED_TOTAL, END_TO_END_LATENCY_SECONDS,
+    FORWARD_DROPPED_TOTAL, FORWARD_FAILED_TOTAL, FORWARD_SENT_TOTAL, Histogram, LATENCY_BUCKETS,
+    MESSAGES_RECEIVED_TOTAL, MetricsWriter, PARSE_FAILURES_TOTAL, RECONNECTS_TOTAL, SUBJECT_LABEL,
+    SYMBOL_LABEL,
+};
+use stock_ticker::types::StockPrice;
+
+/// Metrics the receive loop records beyond `MessageCounters`.
+pub struct ConsumerMetrics {
+    /// Valid ticks by (symbol, subject).
+    received: Mutex<HashMap<(String, String), u64>>,
+    latency: Mutex<Histogram>,
+    pub alert_webhook_failures: AtomicU64,
+}
+
+impl Default for ConsumerMetrics {
+    fn default() -> Self {
+        ConsumerMetrics {
+            received: Mutex::new(HashMap::new()),
+            latency: Mutex::new(Histogram::new(LATENCY_BUCKETS)),
+            alert_webhook_failures: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ConsumerMetrics {
+    pub fn record(&self, subject: &str, stock: &StockPrice) {
+        *self
+            .received
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry((stock.symbol.clone(), subject.to_string()))
+            .or_default() += 1;
+
+        // Ticks without a usable timestamp, or from a clock ahead of ours,
+        // have no meaningful latency
+        if let Ok(sent) = DateTime::parse_from_rfc3339(&stock.timestamp)
+            && let Ok(latency) = (Utc::now() - sent.with_timezone(&Utc)).to_std()
+        {
+            self.latency
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .observe(latency.as_secs_f64());
+        }
+    }
+}
+
+/// Everything a scrape reads.
+#[derive(Clone)]
+pub struct MetricsState {
+    pub metrics: Arc<ConsumerMetrics>,
+    pub counters: Arc<MessageCounters>,
+    pub nats: Option<Arc<async_nats::Statistics>>,
+    pub forward: Option<ForwardStatsHandle>,
+}
+
+/// Serves `/metrics` on `listener` until the process exits.
+pub async fn serve(listener: tokio::net::TcpListener, state: MetricsState) -> std::io::Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(state);
+    axum::serve(listener, app).await
+}
+
+async fn metrics(State(state): State<MetricsState>) -> impl IntoResponse {
+    let snapshot = state.counters.snapshot();
+    let mut out = MetricsWriter::new();
+
+    {
+        let received = state
+            .metrics
+            .received
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let mut rows: Vec<_> = received.iter().collect();
+        rows.sort();
+        let labels: Vec<[(&str, &str); 2]> = rows
+            .iter()
+            .map(|((symbol, subject), _)| {
+                [
+                    (SYMBOL_LABEL, symbol.as_str()),
+                    (SUBJECT_LABEL, subject.as_str()),
+                ]
+            })
+            .collect();
+        out.labelled_counter(
+            MESSAGES_RECEIVED_TOTAL,
+            "Valid ticks received.",
+            labels
+                .iter()
+                .zip(&rows)
+                .map(|(labels, (_, count))| (&labels[..], **count)),
+        );
+    }
+    out.counter(
+        PARSE_FAILURES_TOTAL,
+        "Messages that could not be decoded.",
+        snapshot.parse_failures,
+    );
+    out.counter(
+        DUPLICATES_DROPPED_TOTAL,
+        "Messages dropped as duplicates by Nats-Msg-Id.",
+        snapshot.duplicates,
+    );
+    if let Some(nats) = state.nats.as_ref() {
+        let connects = nats.connects.load(Ordering::Relaxed);
+        out.counter(
+            RECONNECTS_TOTAL,
+            "Reconnections to NATS after the initial connect.",
+            connects.saturating_sub(1),
+        );
+    }
+    out.histogram(
+        END_TO_END_LATENCY_SECONDS,
+        "Time from the tick's timestamp to its receipt.",
+        &state
+            .metrics
+            .latency
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()),
+    );
+    if let Some(forward) = state.forward.as_ref() {
+        let stats = forward.get();
+        out.counter(
+            FORWARD_SENT_TOTAL,
+            "Ticks accepted by the forward webhook.",
+            stats.sent,
+        );
+        out.counter(
+            FORWARD_DROPPED_TOTAL,
+            "Ticks dropped by the forwarder (queue full or breaker open).",
+            stats.dropped,
+        );
+        out.counter(
+            FORWARD_FAILED_TOTAL,
+            "Ticks given up on after every forward retry failed.",
+            stats.failed,
+        );
+    }
+    out.counter(
+        ALERT_WEBHOOK_FAILURES_TOTAL,
+        "Alert webhook deliveries that failed.",
+        state.metrics.alert_webhook_failures.load(Ordering::Relaxed),
+    );
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        out.finish(),
+    )
+}
@@ -0,0 +1,308 @@
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Cell, Paragraph, Row, Table, TableState},
+};
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use stock_ticker::board::{SymbolBoard, SymbolWindow};
+use stock_ticker::counters::MessageCounters;
+use stock_ticker::stats::SymbolStats;
+
+/// How often the table is redrawn when no key is pressed.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// State shared between the receive loop and the dashboard.
+#[derive(Default)]
+pub struct Dashboard {
+    pub board: SymbolBoard,
+    /// Most recent alert, shown in the footer since stdout is suppressed.
+    pub last_alert: Option<String>,
+    /// Most recent warning from background work, such as a failed ack or
+    /// webhook delivery, with how many there have been.
+    pub last_warning: Option<String>,
+    pub warnings: u64,
+    /// Rolling per-symbol statistics, when `--rolling-stats` is set.
+    pub stats: Option<SymbolStats>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    Symbol,
+    Last,
+    Change,
+    Min,
+    Max,
+    Count,
+}
+
+impl SortColumn {
+    const ALL: [SortColumn; 6] = [
+        SortColumn::Symbol,
+        SortColumn::Last,
+        SortColumn::Change,
+        SortColumn::Min,
+        SortColumn::Max,
+        SortColumn::Count,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            SortColumn::Symbol => "Symbol",
+            SortColumn::Last => "Last",
+            SortColumn::Change => "Change %",
+            SortColumn::Min => "Min",
+            SortColumn::Max => "Max",
+            SortColumn::Count => "Count",
+        }
+    }
+}
+
+struct View {
+    sort: SortColumn,
+    descending: bool,
+    table: TableState,
+    page: usize,
+    /// Show rolling statistics instead of the price table.
+    show_stats: bool,
+}
+
+/// Runs the dashboard until the user quits. Blocks the calling thread, so it
+/// must run on a blocking task.
+pub fn run(dashboard: Arc<Mutex<Dashboard>>, counters: Arc<MessageCounters>) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &dashboard, &counters);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    dashboard: &Mutex<Dashboard>,
+    counters: &MessageCounters,
+) -> io::Result<()> {
+    let mut view = View {
+        sort: SortColumn::Symbol,
+        descending: false,
+        table: TableState::default().with_selected(0),
+        page: 1,
+        show_stats: false,
+    };
+
+    loop {
+        terminal.draw(|frame| {
+            let mut dashboard = dashboard.lock().unwrap_or_else(|e| e.into_inner());
+            draw(frame, &mut dashboard, counters, &mut view);
+        })?;
+
+        if !event::poll(REFRESH_INTERVAL)? {
+            continue;
+        }
+
+        // Resize events need no handling beyond the redraw at the top of the loop
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            KeyCode::Char('s') => view.show_stats = !view.show_stats,
+            KeyCode::Char(c @ '1'..='6') => {
+                let column = SortColumn::ALL[c as usize - '1' as usize];
+                if view.sort == column {
+                    view.descending = !view.descending;
+                } else {
+                    view.sort = column;
+                    view.descending = column != SortColumn::Symbol;
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j') => view.table.scroll_down_by(1),
+            KeyCode::Up | KeyCode::Char('k') => view.table.scroll_up_by(1),
+            KeyCode::PageDown => view.table.scroll_down_by(view.page as u16),
+            KeyCode::PageUp => view.table.scroll_up_by(view.page as u16),
+            KeyCode::Home => view.table.select_first(),
+            KeyCode::End => view.table.select_last(),
+            _ => {}
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, dashboard: &mut Dashboard, counters: &MessageCounters, view: &mut View) {
+    let [table_area, footer_area] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(3)]).areas(frame.area());
+
+    if view.show_stats {
+        draw_stats(frame, dashboard.stats.as_mut(), table_area);
+    } else {
+        draw_board(frame, dashboard, view, table_area);
+    }
+
+    let snapshot = counters.snapshot();
+    let footer = vec![
+        Line::from(format!(
+            "received {} | parse failures {} | rejected {} | duplicates {} | 1-6 sort (again to reverse) · ↑↓/PgUp/PgDn scroll · s stats · q quit",
+            snapshot.received, snapshot.parse_failures, snapshot.rejected, snapshot.duplicates
+        )),
+        Line::from(
+            dashboard
+                .last_alert
+                .as_deref()
+                .map(|alert| format!("🚨 {}", alert))
+                .unwrap_or_default(),
+        ),
+        Line::from(
+            dashboard
+                .last_warning
+                .as_deref()
+                .map(|warning| format!("{} ({} warnings)", warning, dashboard.warnings))
+                .unwrap_or_default(),
+        ),
+    ];
+    frame.render_widget(Paragraph::new(footer), footer_area);
+}
+
+fn draw_board(frame: &mut Frame, dashboard: &Dashboard, view: &mut View, table_area: Rect) {
+    let mut rows: Vec<(&str, &SymbolWindow)> = dashboard.board.iter().collect();
+    rows.sort_by(|a, b| {
+        let ordering = match view.sort {
+            SortColumn::Symbol => a.0.cmp(b.0),
+            SortColumn::Last => a.1.last.total_cmp(&b.1.last),
+            SortColumn::Change => a.1.change_percent().total_cmp(&b.1.change_percent()),
+            SortColumn::Min => a.1.min.total_cmp(&b.1.min),
+            SortColumn::Max => a.1.max.total_cmp(&b.1.max),
+            SortColumn::Count => a.1.count.cmp(&b.1.count),
+        };
+        let ordering = if view.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        ordering.then(a.0.cmp(b.0))
+    });
+
+    let header = Row::new(
+        SortColumn::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let marker = match (*column == view.sort, view.descending) {
+                    (true, true) => " ▼",
+                    (true, false) => " ▲",
+                    _ => "",
+                };
+                Cell::from(format!("{} {}{}", i + 1, column.title(), marker))
+            })
+            .chain(std::iter::once(Cell::from("Last 60 ticks"))),
+    )
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let body = rows.iter().map(|(symbol, window)| {
+        let change = window.change_percent();
+        let color = if change > 0.0 {
+            Color::Green
+        } else if change < 0.0 {
+            Color::Red
+        } else {
+            Color::Reset
+        };
+        Row::new(vec![
+            Cell::from(symbol.to_string()),
+            Cell::from(format!("{:.2}", window.last)),
+            Cell::from(format!("{:+.2}", change)).style(Style::default().fg(color)),
+            Cell::from(format!("{:.2}", window.min)),
+            Cell::from(format!("{:.2}", window.max)),
+            Cell::from(window.count.to_string()),
+            Cell::from(sparkline(window)),
+        ])
+    });
+
+    let widths = [
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(9),
+        Constraint::Min(10),
+    ];
+
+    // Rows visible between the borders and the header, used for paging
+    view.page = table_area.height.saturating_sub(3).max(1) as usize;
+
+    let table = Table::new(body, widths)
+        .header(header)
+        .block(Block::bordered().title(format!(" Stock Ticker — {} symbols ", rows.len())))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, table_area, &mut view.table);
+}
+
+fn draw_stats(frame: &mut Frame, stats: Option<&mut SymbolStats>, area: Rect) {
+    let Some(stats) = stats else {
+        let hint =
+            Paragraph::new("Rolling statistics are off; start with --rolling-stats <N|duration>")
+                .block(Block::bordered().title(" Rolling statistics "));
+        frame.render_widget(hint, area);
+        return;
+    };
+
+    let title = format!(" Rolling statistics — {} ", stats.window());
+    let header = Row::new(["Symbol", "Count", "Mean", "Min", "Max", "Std Dev"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let body = stats.snapshot().into_iter().map(|(symbol, s)| {
+        Row::new(vec![
+            Cell::from(symbol),
+            Cell::from(s.count.to_string()),
+            Cell::from(format!("{:.2}", s.mean)),
+            Cell::from(format!("{:.2}", s.min)),
+            Cell::from(format!("{:.2}", s.max)),
+            Cell::from(format!("{:.2}", s.stddev)),
+        ])
+    });
+    let widths = [
+        Constraint::Length(10),
+        Constraint::Length(9),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+    ];
+    let table = Table::new(body, widths)
+        .header(header)
+        .block(Block::bordered().title(title));
+    frame.render_widget(table, area);
+}
+
+fn sparkline(window: &SymbolWindow) -> String {
+    let (low, high) = window
+        .recent
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+            (lo.min(*p), hi.max(*p))
+        });
+    let span = high - low;
+    window
+        .recent
+        .iter()
+        .map(|price| {
+            let level = if span > 0.0 {
+                ((price - low) / span * (SPARK_LEVELS.len() - 1) as f64).round() as usize
+            } else {
+                0
+            };
+            SPARK_LEVELS[level]
+        })
+        .collect()
+}
@@ -1,43 +1,687 @@
-use async_nats::connect;
-use chrono::Utc;
+use chrono::{DateTime, FixedOffset, NaiveTime, Utc};
+use chrono_tz::Tz;
+use clap::{Parser, ValueEnum};
 use rand::distr::{Distribution, Uniform};
-use rand::rng;
-use serde_json;
-use tokio::time::{Duration, sleep};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use rand_distr::Normal;
+use std::{
+    convert::Infallible,
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+use tokio::time::sleep;
 
-use stock_ticker::types::StockPrice;
+#[cfg(feature = "alpha-vantage")]
+use stock_ticker::alpha_vantage::AlphaVantageSource;
+use stock_ticker::batch::{BATCH_COUNT_HEADER, TickBatch, batch_subject};
+use stock_ticker::config::{self, Config};
+use stock_ticker::dispatch::STATUS_SUBJECT;
+use stock_ticker::nats;
+use stock_ticker::price_source::PriceSource;
+use stock_ticker::shutdown::Shutdown;
+use stock_ticker::types::{MarketStatus, StockPrice};
+use stock_ticker::wire::WireFormat;
 
-async fn generate_random_price() -> f64 {
-    let mut rng = rng();
-    let price_range = Uniform::new(100.0, 500.0).expect("Failed to create uniform distribution");
-    price_range.sample(&mut rng)
+/// Lowest price a normal draw is clamped to, so the tail never yields a
+/// zero or negative price.
+const PRICE_FLOOR: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DistributionKind {
+    /// Uniform between $100 and $500
+    Uniform,
+    /// Gaussian around --mean with --stddev, clamped at $0.01
+    Normal,
+}
+
+/// The distribution prices are drawn from.
+enum PriceDistribution {
+    Uniform(Uniform<f64>),
+    Normal(Normal<f64>),
+}
+
+impl PriceDistribution {
+    /// `mean` and `stddev` are already checked by their argument parsers.
+    fn new(kind: DistributionKind, mean: f64, stddev: f64) -> Self {
+        match kind {
+            DistributionKind::Uniform => PriceDistribution::Uniform(
+                Uniform::new(100.0, 500.0).expect("Failed to create uniform distribution"),
+            ),
+            DistributionKind::Normal => PriceDistribution::Normal(
+                Normal::new(mean, stddev).expect("Failed to create normal distribution"),
+            ),
+        }
+    }
+
+    fn generate_random_price(&self, rng: &mut impl Rng) -> f64 {
+        match self {
+            PriceDistribution::Uniform(uniform) => uniform.sample(rng),
+            PriceDistribution::Normal(normal) => normal.sample(rng).max(PRICE_FLOOR),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SourceKind {
+    /// Simulated prices from --distribution
+    Random,
+    /// Live quotes from Alpha Vantage, random prices when they fail; needs
+    /// the alpha-vantage feature and ALPHA_VANTAGE_API_KEY
+    AlphaVantage,
+}
+
+/// Simulated prices, drawn independently of the symbol.
+struct RandomSource {
+    distribution: PriceDistribution,
+    rng: StdRng,
+}
+
+impl PriceSource for RandomSource {
+    type Error = Infallible;
+
+    async fn price(&mut self, _symbol: &str) -> Result<f64, Infallible> {
+        Ok(self.distribution.generate_random_price(&mut self.rng))
+    }
+}
+
+/// The `--source` prices come from. It always yields a price, because a
+/// failed live quote falls back to a random one.
+struct Source {
+    random: RandomSource,
+    #[cfg(feature = "alpha-vantage")]
+    alpha_vantage: Option<AlphaVantageSource>,
+}
+
+impl PriceSource for Source {
+    type Error = Infallible;
+
+    async fn price(&mut self, symbol: &str) -> Result<f64, Infallible> {
+        #[cfg(feature = "alpha-vantage")]
+        if let Some(live) = self.alpha_vantage.as_mut() {
+            match live.price(symbol).await {
+                Ok(price) => return Ok(price),
+                Err(e) => eprintln!("⚠️  {}; using a random price for {}", e, symbol),
+            }
+        }
+        self.random.price(symbol).await
+    }
+}
+
+/// A daily trading window such as `09:30-16:00`; a close earlier than the
+/// open wraps past midnight.
+#[derive(Debug, Clone, Copy)]
+struct MarketHours {
+    open: NaiveTime,
+    close: NaiveTime,
+}
+
+impl MarketHours {
+    fn is_open(&self, now: NaiveTime) -> bool {
+        if self.open < self.close {
+            self.open <= now && now < self.close
+        } else {
+            now >= self.open || now < self.close
+        }
+    }
+}
+
+impl FromStr for MarketHours {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (open, close) = s
+            .split_once('-')
+            .ok_or_else(|| format!("expected HH:MM-HH:MM, got '{}'", s))?;
+        let time = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .map_err(|_| format!("'{}' is not a HH:MM time", t.trim()))
+        };
+        let hours = MarketHours {
+            open: time(open)?,
+            close: time(close)?,
+        };
+        if hours.open == hours.close {
+            return Err("the market must open and close at different times".to_string());
+        }
+        Ok(hours)
+    }
+}
+
+impl fmt::Display for MarketHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.open.format("%H:%M"),
+            self.close.format("%H:%M")
+        )
+    }
+}
+
+/// Recorded gap before the first tick again when `--loop` starts over and the
+/// file has no gap of its own to reuse.
+const LOOP_GAP: Duration = Duration::from_secs(1);
+
+/// Recorded ticks read by `--replay`, handed out in file order.
+struct Replay {
+    ticks: Vec<StockPrice>,
+    /// When each tick was recorded, if its timestamp parses.
+    times: Vec<Option<DateTime<FixedOffset>>>,
+    next: usize,
+    /// Set once the file has started over.
+    looped: bool,
+    speed: Option<f64>,
+    repeat: bool,
+}
+
+impl Replay {
+    /// Reads a `.csv` file with a header row, or JSON lines otherwise.
+    /// Every tick is validated up front so a bad record fails the run
+    /// before anything is published.
+    fn load(path: &Path, speed: Option<f64>, repeat: bool) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let ticks = if csv {
+            read_csv(file)?
+        } else {
+            read_json_lines(file)?
+        };
+        if ticks.is_empty() {
+            return Err("no ticks in file".to_string());
+        }
+        Ok(Replay::new(ticks, speed, repeat))
+    }
+
+    fn new(ticks: Vec<StockPrice>, speed: Option<f64>, repeat: bool) -> Self {
+        let times = ticks
+            .iter()
+            .map(|tick| DateTime::parse_from_rfc3339(&tick.timestamp).ok())
+            .collect();
+        Replay {
+            ticks,
+            times,
+            next: 0,
+            looped: false,
+            speed,
+            repeat,
+        }
+    }
+
+    /// The next tick and how long to wait before publishing it, or `None`
+    /// once the file is done and not looping.
+    ///
+    /// Without a speed every tick goes out immediately. With one, the
+    /// recorded gap to the previous tick is divided by it; ticks whose
+    /// timestamps are missing or go backwards are not delayed. Starting over
+    /// waits the file's first gap, or `LOOP_GAP` when it has none.
+    fn next(&mut self) -> Option<(StockPrice, Duration)> {
+        if self.next == self.ticks.len() {
+            if !self.repeat {
+                return None;
+            }
+            self.next = 0;
+            self.looped = true;
+        }
+        let index = self.next;
+        self.next += 1;
+        let gap = match index.checked_sub(1) {
+            Some(previous) => self.recorded_gap(previous, index),
+            None if self.looped => self.recorded_gap(0, 1).or(Some(LOOP_GAP)),
+            None => None,
+        };
+        let delay = match (self.speed, gap) {
+            (Some(speed), Some(gap)) => gap.div_f64(speed),
+            _ => Duration::ZERO,
+        };
+        Some((self.ticks[index].clone(), delay))
+    }
+
+    /// Time between two recorded ticks, if both timestamps parse and
+    /// `to` is not earlier.
+    fn recorded_gap(&self, from: usize, to: usize) -> Option<Duration> {
+        let (Some(from), Some(to)) = (*self.times.get(from)?, *self.times.get(to)?) else {
+            return None;
+        };
+        (to - from).to_std().ok()
+    }
+}
+
+fn read_json_lines(file: impl Read) -> Result<Vec<StockPrice>, String> {
+    let mut ticks = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let tick = StockPrice::try_from(line.as_bytes())
+            .map_err(|e| format!("line {}: {}", index + 1, e))?;
+        ticks.push(tick);
+    }
+    Ok(ticks)
+}
+
+fn read_csv(file: impl Read) -> Result<Vec<StockPrice>, String> {
+    let mut reader = csv::Reader::from_reader(file);
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    let mut ticks = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        let line = record.position().map_or(0, |position| position.line());
+        let tick = record
+            .deserialize::<StockPrice>(Some(&headers))
+            .map_err(|e| e.to_string())
+            .and_then(|tick| tick.validated().map_err(|e| e.to_string()))
+            .map_err(|e| format!("line {}: {}", line, e))?;
+        ticks.push(tick);
+    }
+    Ok(ticks)
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Publish simulated stock prices")]
+struct Args {
+    /// TOML config file; environment variables override its values
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Identifies this publisher in every tick, so consumers can track
+    /// sequence gaps per publisher (default: publisher-<pid>)
+    #[arg(long, env = "PUBLISHER_ID")]
+    publisher_id: Option<String>,
+
+    /// Publish each round of ticks as one batch message on
+    /// "<subject>.batch" instead of one message per tick
+    #[arg(long)]
+    batch: bool,
+
+    /// Publish this many rounds (one tick per symbol each) and exit,
+    /// instead of running until stopped
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    ticks: Option<u64>,
+
+    /// Seed the price generator so runs produce the same prices
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Where prices come from
+    #[arg(long, value_enum, default_value_t = SourceKind::Random)]
+    source: SourceKind,
+
+    /// Distribution prices are drawn from
+    #[arg(long, value_enum, default_value_t = DistributionKind::Uniform)]
+    distribution: DistributionKind,
+
+    /// Mean price for --distribution normal
+    #[arg(long, default_value_t = 300.0, value_parser = parse_mean)]
+    mean: f64,
+
+    /// Standard deviation for --distribution normal
+    #[arg(long, default_value_t = 50.0, value_parser = parse_stddev)]
+    stddev: f64,
+
+    /// Only publish within this daily window, e.g. "09:30-16:00"; outside
+    /// it ticks pause and "closed" is announced on market_status
+    #[arg(long, value_name = "HH:MM-HH:MM")]
+    market_hours: Option<MarketHours>,
+
+    /// Time zone of --market-hours, e.g. "America/New_York"
+    #[arg(long, requires = "market_hours", default_value = "UTC")]
+    market_tz: Tz,
+
+    /// Publish the ticks recorded in this CSV or JSON lines file instead
+    /// of simulated prices
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["batch", "ticks", "seed", "source", "distribution", "market_hours"]
+    )]
+    replay: Option<PathBuf>,
+
+    /// Honour the recorded gaps between replayed ticks, divided by this
+    /// factor (1 = as recorded); without it ticks go out back to back
+    #[arg(long, requires = "replay", value_parser = parse_speed)]
+    speed: Option<f64>,
+
+    /// Start the replay file over when it ends instead of exiting
+    #[arg(long = "loop", requires = "replay")]
+    repeat: bool,
+}
+
+fn parse_mean(s: &str) -> Result<f64, String> {
+    let mean: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    if !(mean.is_finite() && mean > 0.0) {
+        return Err("the mean must be a positive price".to_string());
+    }
+    Ok(mean)
+}
+
+fn parse_stddev(s: &str) -> Result<f64, String> {
+    let stddev: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    if !(stddev.is_finite() && stddev >= 0.0) {
+        return Err("the standard deviation must be zero or positive".to_string());
+    }
+    Ok(stddev)
+}
+
+fn parse_speed(s: &str) -> Result<f64, String> {
+    let speed: f64 = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err("the speed must be positive".to_string());
+    }
+    Ok(speed)
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    config::load_dotenv();
+    let args = Args::parse();
+    let config = Config::load(args.config.as_deref())?;
+    let replay = args.replay.as_deref().map(|path| {
+        Replay::load(path, args.speed, args.repeat).unwrap_or_else(|e| {
+            eprintln!("❌ Cannot replay {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
+    let random = RandomSource {
+        distribution: PriceDistribution::new(args.distribution, args.mean, args.stddev),
+        rng: match args.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        },
+    };
+    let mut source = match args.source {
+        SourceKind::Random => Source {
+            random,
+            #[cfg(feature = "alpha-vantage")]
+            alpha_vantage: None,
+        },
+        #[cfg(feature = "alpha-vantage")]
+        SourceKind::AlphaVantage => match AlphaVantageSource::from_env() {
+            Ok(live) => Source {
+                random,
+                alpha_vantage: Some(live),
+            },
+            Err(e) => {
+                eprintln!("❌ Cannot use Alpha Vantage: {}", e);
+                std::process::exit(1);
+            }
+        },
+        #[cfg(not(feature = "alpha-vantage"))]
+        SourceKind::AlphaVantage => {
+            eprintln!("❌ --source alpha-vantage needs a build with --features alpha-vantage");
+            std::process::exit(1);
+        }
+    };
+    let publisher_id = args
+        .publisher_id
+        .or(config.publisher.id)
+        .unwrap_or_else(|| format!("publisher-{}", std::process::id()));
+
     // Connect to NATS server asynchronously
-    let client = connect("nats://127.0.0.1:4222").await?;
+    let client = nats::connect(&config.nats.url).await?;
+
+    let subject = config.nats.subject;
+    let format = WireFormat::from_env()?;
+    println!(
+        "Publishing {} to '{}' as '{}'...",
+        format, subject, publisher_id
+    );
+
+    let symbols = config.publisher.symbols;
+    let mut seq: u64 = 0;
+    let mut published: u64 = 0;
+    let mut rounds: u64 = 0;
+    let shutdown = Shutdown::on_signals();
 
-    let symbols = vec!["AAPL", "GOOGL", "AMZN", "MSFT", "TSLA"];
+    if let Some(mut replay) = replay {
+        println!("⏪ Replaying {} recorded ticks", replay.ticks.len());
+        while let Some((recorded, delay)) = replay.next() {
+            if !delay.is_zero() {
+                tokio::select! {
+                    _ = sleep(delay) => {}
+                    _ = shutdown.triggered() => break,
+                }
+            } else if shutdown.is_triggered() {
+                break;
+            }
 
-    loop {
-        for symbol in &symbols {
+            seq += 1;
             let stock_price = StockPrice {
-                symbol: symbol.to_string(),
-                price: generate_random_price().await,
                 timestamp: Utc::now().to_rfc3339(),
+                seq,
+                publisher_id: publisher_id.clone(),
+                ..recorded
             };
+            client
+                .publish(subject.clone(), format.encode(&stock_price)?.into())
+                .await?;
+            published += 1;
+            println!("📤 Published: {:?}", stock_price);
+        }
+    } else {
+        let batch_subject = batch_subject(&subject);
+        let mut market_open = None;
+        loop {
+            if let Some(hours) = args.market_hours {
+                let open = hours.is_open(Utc::now().with_timezone(&args.market_tz).time());
+                // Announce each change once rather than on every round
+                if market_open != Some(open) {
+                    market_open = Some(open);
+                    let status = MarketStatus {
+                        status: if open { "open" } else { "closed" }.to_string(),
+                        timestamp: Utc::now().to_rfc3339(),
+                    };
+                    client
+                        .publish(STATUS_SUBJECT, format.encode(&status)?.into())
+                        .await?;
+                    published += 1;
+                    if open {
+                        println!("🏛️  Market open ({} {})", hours, args.market_tz);
+                    } else {
+                        println!(
+                            "🏛️  Market closed outside {} {}; pausing ticks",
+                            hours, args.market_tz
+                        );
+                    }
+                }
+                if !open {
+                    tokio::select! {
+                        _ = sleep(config.publisher.interval) => continue,
+                        _ = shutdown.triggered() => break,
+                    }
+                }
+            }
 
-            // Serialize struct to JSON
-            let message = serde_json::to_string(&stock_price)?;
+            let mut batch = Vec::with_capacity(symbols.len());
+            for symbol in &symbols {
+                seq += 1;
+                let Ok(price) = source.price(symbol).await;
+                let stock_price = StockPrice {
+                    symbol: symbol.to_string(),
+                    price,
+                    timestamp: Utc::now().to_rfc3339(),
+                    seq,
+                    publisher_id: publisher_id.clone(),
+                };
 
-            // Publish to NATS asynchronously
-            client.publish("stock_prices", message.into()).await?;
+                if args.batch {
+                    batch.push(stock_price);
+                    continue;
+                }
 
-            println!("📤 Published: {:?}", stock_price);
+                // Serialize struct in the configured wire format (JSON by default)
+                let message = format.encode(&stock_price)?;
+
+                // Publish to NATS asynchronously
+                client.publish(subject.clone(), message.into()).await?;
+                published += 1;
+
+                println!("📤 Published: {:?}", stock_price);
+            }
+
+            if args.batch {
+                let mut headers = async_nats::HeaderMap::new();
+                headers.insert(BATCH_COUNT_HEADER, batch.len().to_string());
+                let message = format.encode(&TickBatch { ticks: batch })?;
+                client
+                    .publish_with_headers(batch_subject.clone(), headers, message.into())
+                    .await?;
+                published += 1;
+                println!(
+                    "📤 Published batch of {} ticks to '{}'",
+                    symbols.len(),
+                    batch_subject
+                );
+            }
+
+            rounds += 1;
+            if args.ticks.is_some_and(|ticks| rounds >= ticks) {
+                break;
+            }
+
+            // Sleep asynchronously before generating new prices; a signal only
+            // stops the publisher between rounds, so a batch is never cut short
+            tokio::select! {
+                _ = sleep(config.publisher.interval) => {}
+                _ = shutdown.triggered() => break,
+            }
         }
+    }
+
+    // Publishes are buffered by the client; make sure they reach the server
+    client.flush().await?;
+    println!(
+        "🛑 Stopped after publishing {} messages ({} ticks)",
+        published, seq
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(symbol: &str, timestamp: &str) -> StockPrice {
+        StockPrice {
+            symbol: symbol.to_string(),
+            price: 100.0,
+            timestamp: timestamp.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn delays(replay: &mut Replay, count: usize) -> Vec<Duration> {
+        (0..count)
+            .map(|_| replay.next().map(|(_, delay)| delay).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn recorded_gaps_are_divided_by_the_speed() {
+        let ticks = vec![
+            tick("AAPL", "2025-03-12T14:30:00Z"),
+            tick("MSFT", "2025-03-12T14:30:02Z"),
+            tick("AAPL", "2025-03-12T14:30:06Z"),
+        ];
+        let mut replay = Replay::new(ticks, Some(2.0), false);
+        assert_eq!(
+            delays(&mut replay, 3),
+            [
+                Duration::ZERO,
+                Duration::from_secs(1),
+                Duration::from_secs(2)
+            ]
+        );
+        assert!(replay.next().is_none());
+    }
+
+    #[test]
+    fn missing_and_backwards_timestamps_are_not_delayed() {
+        let ticks = vec![
+            tick("AAPL", "2025-03-12T14:30:05Z"),
+            tick("MSFT", "2025-03-12T14:30:00Z"),
+            tick("AAPL", ""),
+        ];
+        let mut replay = Replay::new(ticks, Some(1.0), false);
+        assert_eq!(delays(&mut replay, 3), [Duration::ZERO; 3]);
+    }
+
+    #[test]
+    fn no_speed_means_no_delay() {
+        let ticks = vec![
+            tick("AAPL", "2025-03-12T14:30:00Z"),
+            tick("MSFT", "2025-03-12T14:30:02Z"),
+        ];
+        let mut replay = Replay::new(ticks, None, true);
+        assert_eq!(delays(&mut replay, 4), [Duration::ZERO; 4]);
+    }
+
+    #[test]
+    fn looping_waits_the_first_gap() {
+        let ticks = vec![
+            tick("AAPL", "2025-03-12T14:30:00Z"),
+            tick("MSFT", "2025-03-12T14:30:03Z"),
+        ];
+        let mut replay = Replay::new(ticks, Some(1.0), true);
+        assert_eq!(
+            delays(&mut replay, 4),
+            [
+                Duration::ZERO,
+                Duration::from_secs(3),
+                Duration::from_secs(3),
+                Duration::from_secs(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn looping_a_single_tick_waits_the_fixed_gap() {
+        let mut replay = Replay::new(vec![tick("AAPL", "2025-03-12T14:30:00Z")], Some(4.0), true);
+        assert_eq!(
+            delays(&mut replay, 2),
+            [Duration::ZERO, LOOP_GAP.div_f64(4.0)]
+        );
+    }
+
+    #[test]
+    fn json_lines_skip_blank_lines() {
+        let input =
+            "{\"symbol\":\"AAPL\",\"price\":1.5}\n\n{\"symbol\":\"MSFT\",\"price\":\"2\"}\n";
+        let ticks = read_json_lines(input.as_bytes()).unwrap();
+        let symbols: Vec<_> = ticks
+            .iter()
+            .map(|tick| (tick.symbol.as_str(), tick.price))
+            .collect();
+        assert_eq!(symbols, [("AAPL", 1.5), ("MSFT", 2.0)]);
+    }
+
+    #[test]
+    fn bad_json_line_is_reported_by_number() {
+        let input = "{\"symbol\":\"AAPL\",\"price\":1.5}\n{bad\n";
+        let error = read_json_lines(input.as_bytes()).unwrap_err();
+        assert!(error.starts_with("line 2:"), "{}", error);
+    }
+
+    #[test]
+    fn csv_records_are_read_by_header() {
+        let input = "timestamp,symbol,price\n2025-03-12T14:30:00Z,AAPL,1.5\n,MSFT,2\n";
+        let ticks = read_csv(input.as_bytes()).unwrap();
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[0].symbol, "AAPL");
+        assert_eq!(ticks[0].timestamp, "2025-03-12T14:30:00Z");
+        assert_eq!(ticks[1].price, 2.0);
+    }
 
-        // Sleep asynchronously for 1 second before generating new prices
-        sleep(Duration::from_secs(2)).await;
+    #[test]
+    fn invalid_csv_record_is_reported_by_line() {
+        let input = "symbol,price\nAAPL,1.5\nMSFT,-2\n";
+        let error = read_csv(input.as_bytes()).unwrap_err();
+        assert!(error.starts_with("line 3:"), "{}", error);
     }
 }
@@ -0,0 +1,85 @@
+use clap::Parser;
+use futures::{StreamExt, stream};
+use std::{collections::HashMap, path::PathBuf};
+
+use stock_ticker::batch::batch_subject;
+use stock_ticker::config::{self, Config};
+use stock_ticker::nats;
+use stock_ticker::quote::{QUOTE_SUBJECT, QuoteReply};
+use stock_ticker::subscriber::{SubscribeError, decode_nats_message};
+use stock_ticker::types::{PayloadError, StockPrice};
+use stock_ticker::wire::WireFormat;
+
+#[derive(Parser, Debug)]
+#[command(about = "Answer quote requests with the latest tick per symbol")]
+struct Args {
+    /// TOML config file; environment variables override its values
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+}
+
+/// Answers `quote.request` with the latest tick seen for a symbol on the tick subject.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    config::load_dotenv();
+    let args = Args::parse();
+    let config = Config::load(args.config.as_deref())?;
+    let client = nats::connect(&config.nats.url).await?;
+    println!("Connected to NATS at {}", config.nats.url);
+
+    let format = WireFormat::from_env()?;
+    let subject = config.nats.subject;
+    let mut ticks = stream::select_all([
+        client.subscribe(batch_subject(&subject)).await?,
+        client.subscribe(subject).await?,
+    ]);
+    let mut requests = client.subscribe(QUOTE_SUBJECT).await?;
+    println!("Answering quote requests on '{}'...", QUOTE_SUBJECT);
+
+    let mut latest: HashMap<String, StockPrice> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            Some(message) = ticks.next() => {
+                for tick in decode_nats_message(format, &message) {
+                    match tick {
+                        Ok(stock) => {
+                            // Requests are looked up uppercased
+                            latest.insert(stock.symbol.to_uppercase(), stock);
+                        }
+                        Err(SubscribeError::Payload(PayloadError::Invalid { .. })) => {}
+                        Err(e) => eprintln!("❌ Failed to parse message: {}", e),
+                    }
+                }
+            }
+            Some(request) = requests.next() => {
+                let Some(reply_to) = request.reply else {
+                    continue;
+                };
+                let symbol = String::from_utf8_lossy(&request.payload).trim().to_uppercase();
+                let reply = match latest.get(&symbol) {
+                    Some(stock) => QuoteReply::Quote(stock.clone()),
+                    None => QuoteReply::Error {
+                        error: format!("unknown symbol '{}'", symbol),
+                    },
+                };
+                // A failed reply only affects this request; keep serving
+                let body = match serde_json::to_vec(&reply) {
+                    Ok(body) => body,
+                    Err(e) => {
+                        eprintln!("❌ Failed to encode quote for {}: {}", symbol, e);
+                        continue;
+                    }
+                };
+                let logged = String::from_utf8_lossy(&body).into_owned();
+                match client.publish(reply_to, body.into()).await {
+                    Ok(()) => println!("💬 Quote for {}: {}", symbol, logged),
+                    Err(e) => eprintln!("❌ Failed to reply to quote request for {}: {}", symbol, e),
+                }
+            }
+            else => break,
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::types::StockPrice;
+
+/// Number of recent prices kept per symbol for sparklines.
+pub const WINDOW_SIZE: usize = 60;
+
+/// Rolling view of one symbol's recent ticks.
+#[derive(Debug, Clone)]
+pub struct SymbolWindow {
+    pub last: f64,
+    pub previous: Option<f64>,
+    pub min: f64,
+    pub max: f64,
+    pub count: u64,
+    pub recent: VecDeque<f64>,
+}
+
+impl SymbolWindow {
+    fn new(price: f64) -> Self {
+        SymbolWindow {
+            last: price,
+            previous: None,
+            min: price,
+            max: price,
+            count: 0,
+            recent: VecDeque::with_capacity(WINDOW_SIZE),
+        }
+    }
+
+    fn push(&mut self, price: f64) {
+        if self.count > 0 {
+            self.previous = Some(self.last);
+        }
+        self.last = price;
+        self.min = self.min.min(price);
+        self.max = self.max.max(price);
+        self.count += 1;
+        if self.recent.len() == WINDOW_SIZE {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(price);
+    }
+
+    /// Percent change of the last tick relative to the one before it.
+    pub fn change_percent(&self) -> f64 {
+        match self.previous {
+            Some(previous) if previous != 0.0 => (self.last - previous) / previous * 100.0,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Per-symbol rolling windows shared by the TUI and the plain-text output.
+#[derive(Debug, Default)]
+pub struct SymbolBoard {
+    symbols: HashMap<String, SymbolWindow>,
+}
+
+impl SymbolBoard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Folds a tick into its symbol's window and returns the price it replaced.
+    pub fn update(&mut self, stock: &StockPrice) -> Option<f64> {
+        let window = self
+            .symbols
+            .entry(stock.symbol.clone())
+            .or_insert_with(|| SymbolWindow::new(stock.price));
+        window.push(stock.price);
+        window.previous
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&SymbolWindow> {
+        self.symbols.get(symbol)
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SymbolWindow)> {
+        self.symbols.iter().map(|(s, w)| (s.as_str(), w))
+    }
+
+    /// The `n` most active symbols, highest tick count first and ties by name.
+    pub fn top_by_count(&self, n: usize) -> Vec<(&str, u64)> {
+        let mut counts: Vec<(&str, u64)> = self.iter().map(|(s, w)| (s, w.count)).collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts.truncate(n);
+        counts
+    }
+}
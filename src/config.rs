@@ -0,0 +1,271 @@
+use serde::{Deserialize, Deserializer};
+use std::{
+    env, fmt, fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use crate::auth_client::DEFAULT_AUTH_URL;
+use crate::nats::{DEFAULT_SUBJECT, NATS_URL, SUBJECT_ENV};
+
+/// Overrides `nats.url`.
+pub const NATS_URL_ENV: &str = "NATS_URL";
+/// Overrides `publisher.id`.
+pub const PUBLISHER_ID_ENV: &str = "PUBLISHER_ID";
+/// Overrides `publisher.symbols`, comma-separated.
+pub const PUBLISHER_SYMBOLS_ENV: &str = "PUBLISHER_SYMBOLS";
+/// Overrides `publisher.interval`, e.g. "500ms".
+pub const PUBLISH_INTERVAL_ENV: &str = "PUBLISH_INTERVAL";
+/// Overrides `consumer.auth_url`.
+pub const AUTH_URL_ENV: &str = "AUTH_URL";
+/// Overrides `consumer.symbols`, comma-separated.
+pub const CONSUMER_SYMBOLS_ENV: &str = "CONSUMER_SYMBOLS";
+/// Overrides `consumer.reauth_interval`, e.g. "5m".
+pub const REAUTH_INTERVAL_ENV: &str = "REAUTH_INTERVAL";
+/// Overrides `auth_service.bind`.
+pub const AUTH_BIND_ENV: &str = "AUTH_BIND";
+
+/// Symbols the publisher simulates by default.
+pub const DEFAULT_SYMBOLS: [&str; 5] = ["AAPL", "GOOGL", "AMZN", "MSFT", "TSLA"];
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    /// An override variable holding a value its setting cannot take.
+    Env {
+        var: &'static str,
+        value: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "cannot read config file {}: {}", path.display(), source)
+            }
+            ConfigError::Parse { path, source } => {
+                write!(f, "invalid config file {}: {}", path.display(), source)
+            }
+            ConfigError::Env { var, value, reason } => {
+                write!(f, "invalid {}='{}': {}", var, value, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            ConfigError::Parse { source, .. } => Some(source),
+            ConfigError::Env { .. } => None,
+        }
+    }
+}
+
+/// Settings shared by every binary, loaded from an optional TOML file.
+///
+/// Each binary reads its own table plus `[nats]`; unknown keys are
+/// rejected so typos surface instead of silently falling back to
+/// defaults. See `config.example.toml` for every key.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub nats: NatsConfig,
+    pub publisher: PublisherConfig,
+    pub consumer: ConsumerConfig,
+    pub auth_service: AuthServiceConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NatsConfig {
+    pub url: String,
+    /// The tick subject; batches go to its `.batch` subject.
+    pub subject: String,
+}
+
+impl Default for NatsConfig {
+    fn default() -> Self {
+        NatsConfig {
+            url: NATS_URL.to_string(),
+            subject: DEFAULT_SUBJECT.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PublisherConfig {
+    /// Publisher ID stamped on every tick; `publisher-<pid>` when unset.
+    pub id: Option<String>,
+    pub symbols: Vec<String>,
+    /// Pause between rounds of ticks, e.g. "2s".
+    #[serde(deserialize_with = "duration")]
+    pub interval: Duration,
+}
+
+impl Default for PublisherConfig {
+    fn default() -> Self {
+        PublisherConfig {
+            id: None,
+            symbols: DEFAULT_SYMBOLS.iter().map(|s| s.to_string()).collect(),
+            interval: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsumerConfig {
+    pub auth_url: String,
+    /// Only keep ticks for these symbols; empty keeps every symbol.
+    pub symbols: Vec<String>,
+    /// How often authorization is re-checked; "0s" disables re-checks.
+    #[serde(deserialize_with = "duration")]
+    pub reauth_interval: Duration,
+}
+
+impl Default for ConsumerConfig {
+    fn default() -> Self {
+        ConsumerConfig {
+            auth_url: DEFAULT_AUTH_URL.to_string(),
+            symbols: Vec::new(),
+            reauth_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthServiceConfig {
+    pub bind: SocketAddr,
+}
+
+impl Default for AuthServiceConfig {
+    fn default() -> Self {
+        AuthServiceConfig {
+            bind: SocketAddr::from(([127, 0, 0, 1], 3001)),
+        }
+    }
+}
+
+impl Config {
+    /// Loads `path` when given, then applies environment overrides.
+    ///
+    /// Precedence is environment over file over defaults; binaries apply
+    /// their command-line flags on top.
+    pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+        let mut config = match path {
+            Some(path) => Config::from_file(path)?,
+            None => Config::default(),
+        };
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Config, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        override_with(&mut self.nats.url, NATS_URL_ENV, Ok)?;
+        override_with(&mut self.nats.subject, SUBJECT_ENV, Ok)?;
+        if let Some(id) = env_value(PUBLISHER_ID_ENV) {
+            self.publisher.id = Some(id);
+        }
+        override_with(
+            &mut self.publisher.symbols,
+            PUBLISHER_SYMBOLS_ENV,
+            symbol_list,
+        )?;
+        override_with(
+            &mut self.publisher.interval,
+            PUBLISH_INTERVAL_ENV,
+            parse_duration,
+        )?;
+        override_with(&mut self.consumer.auth_url, AUTH_URL_ENV, Ok)?;
+        override_with(
+            &mut self.consumer.symbols,
+            CONSUMER_SYMBOLS_ENV,
+            symbol_list,
+        )?;
+        override_with(
+            &mut self.consumer.reauth_interval,
+            REAUTH_INTERVAL_ENV,
+            parse_duration,
+        )?;
+        override_with(&mut self.auth_service.bind, AUTH_BIND_ENV, |value| {
+            SocketAddr::from_str(&value).map_err(|e| e.to_string())
+        })?;
+        Ok(())
+    }
+}
+
+/// Loads `.env` from the working directory or a parent into the
+/// environment, leaving variables that are already set alone. A missing
+/// file is not an error; an unreadable or malformed one is reported and
+/// skipped.
+pub fn load_dotenv() {
+    if let Err(e) = dotenvy::dotenv()
+        && !e.not_found()
+    {
+        eprintln!("⚠️  Ignoring .env: {}", e);
+    }
+}
+
+/// Replaces `setting` with the parsed value of `var` when it is set and
+/// not empty.
+fn override_with<T>(
+    setting: &mut T,
+    var: &'static str,
+    parse: impl FnOnce(String) -> Result<T, String>,
+) -> Result<(), ConfigError> {
+    let Some(value) = env_value(var) else {
+        return Ok(());
+    };
+    *setting = parse(value.clone()).map_err(|reason| ConfigError::Env { var, value, reason })?;
+    Ok(())
+}
+
+/// The value of `var`, treating an empty variable as unset.
+pub(crate) fn env_value(var: &str) -> Option<String> {
+    env::var(var).ok().filter(|value| !value.is_empty())
+}
+
+fn symbol_list(value: String) -> Result<Vec<String>, String> {
+    Ok(value
+        .split(',')
+        .map(str::trim)
+        .filter(|symbol| !symbol.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+fn parse_duration(value: String) -> Result<Duration, String> {
+    humantime::parse_duration(&value).map_err(|e| e.to_string())
+}
+
+/// Reads a duration written as a string such as "2s" or "5m".
+fn duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_duration(value).map_err(serde::de::Error::custom)
+}
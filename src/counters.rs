@@ -0,0 +1,142 @@
+use serde::Serialize;
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Number of symbols listed in a throughput summary line.
+pub const TOP_SYMBOLS: usize = 5;
+
+/// Message counters updated from the receive loop.
+///
+/// Plain relaxed atomics, so they can be shared with reporting tasks
+/// without contending with the hot path.
+#[derive(Debug, Default)]
+pub struct MessageCounters {
+    pub received: AtomicU64,
+    pub parse_failures: AtomicU64,
+    /// Messages that parsed but failed `StockPrice::validate`.
+    pub rejected: AtomicU64,
+    /// Messages dropped because their `Nats-Msg-Id` was seen recently.
+    pub duplicates: AtomicU64,
+    /// Messages on a subject no dispatcher route covers.
+    pub unknown_subjects: AtomicU64,
+    /// Valid ticks dropped by the symbol or price filters.
+    pub filtered: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CounterSnapshot {
+    pub received: u64,
+    pub parse_failures: u64,
+    pub rejected: u64,
+    pub duplicates: u64,
+    pub unknown_subjects: u64,
+    pub filtered: u64,
+}
+
+impl MessageCounters {
+    pub fn snapshot(&self) -> CounterSnapshot {
+        CounterSnapshot {
+            received: self.received.load(Ordering::Relaxed),
+            parse_failures: self.parse_failures.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            duplicates: self.duplicates.load(Ordering::Relaxed),
+            unknown_subjects: self.unknown_subjects.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Formats periodic throughput summaries from successive counter snapshots.
+pub struct ThroughputReporter {
+    started: Instant,
+    last_at: Instant,
+    last_received: u64,
+}
+
+impl Default for ThroughputReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThroughputReporter {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        ThroughputReporter {
+            started: now,
+            last_at: now,
+            last_received: 0,
+        }
+    }
+
+    /// Summary of the interval since the previous call.
+    pub fn interval_line(&mut self, snapshot: CounterSnapshot, top: &[(&str, u64)]) -> String {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_at).as_secs_f64();
+        let delta = snapshot.received - self.last_received;
+        self.last_at = now;
+        self.last_received = snapshot.received;
+
+        format_line(rate(delta, elapsed), snapshot, top)
+    }
+
+    /// Summary of the whole run, averaged since the reporter was created.
+    pub fn total_line(&self, snapshot: CounterSnapshot, top: &[(&str, u64)]) -> String {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        format_line(rate(snapshot.received, elapsed), snapshot, top)
+    }
+}
+
+fn rate(count: u64, secs: f64) -> f64 {
+    if secs > 0.0 { count as f64 / secs } else { 0.0 }
+}
+
+fn format_line(rate: f64, snapshot: CounterSnapshot, top: &[(&str, u64)]) -> String {
+    let top = top
+        .iter()
+        .map(|(symbol, count)| format!("{}={}", symbol, count))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "{:.1} msg/s | total {} | parse failures {} | rejected {} | duplicates {} | unknown subjects {} | filtered {} | top: {}",
+        rate,
+        snapshot.received,
+        snapshot.parse_failures,
+        snapshot.rejected,
+        snapshot.duplicates,
+        snapshot.unknown_subjects,
+        snapshot.filtered,
+        top
+    )
+}
+
+/// Machine-readable summary of a finished run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub received: u64,
+    pub parse_failures: u64,
+    pub rejected: u64,
+    pub duplicates: u64,
+    pub unknown_subjects: u64,
+    pub filtered: u64,
+    pub duration_secs: f64,
+    pub msgs_per_sec: f64,
+}
+
+impl RunSummary {
+    pub fn new(snapshot: CounterSnapshot, elapsed: Duration) -> Self {
+        let duration_secs = elapsed.as_secs_f64();
+        RunSummary {
+            received: snapshot.received,
+            parse_failures: snapshot.parse_failures,
+            rejected: snapshot.rejected,
+            duplicates: snapshot.duplicates,
+            unknown_subjects: snapshot.unknown_subjects,
+            filtered: snapshot.filtered,
+            duration_secs,
+            msgs_per_sec: rate(snapshot.received, duration_secs),
+        }
+    }
+}
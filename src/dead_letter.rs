@@ -0,0 +1,134 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::Utc;
+use serde::Serialize;
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+#[derive(Serialize)]
+struct DeadLetter<'a> {
+    received_at: String,
+    subject: &'a str,
+    error: String,
+    /// The payload as received, or base64 when it is not valid UTF-8.
+    payload: String,
+    /// `base64` when `payload` is encoded, absent otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload_encoding: Option<&'static str>,
+}
+
+/// JSON Lines file of messages that could not be parsed, kept for later
+/// inspection.
+///
+/// Each line is written and flushed on its own, since dead letters are rare
+/// and most useful right after something went wrong.
+pub struct DeadLetterFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    failed: u64,
+    /// Count write failures without logging them, e.g. while a TUI owns the
+    /// terminal.
+    quiet: bool,
+}
+
+impl DeadLetterFile {
+    pub fn open(path: &Path, quiet: bool) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(DeadLetterFile {
+            path: path.to_path_buf(),
+            file,
+            written: 0,
+            failed: 0,
+            quiet,
+        })
+    }
+
+    /// Appends the raw payload and why it was rejected. Write failures are
+    /// counted and logged unless quiet, never returned.
+    pub fn write(&mut self, subject: &str, payload: &[u8], error: &dyn fmt::Display) {
+        let (payload, payload_encoding) = match std::str::from_utf8(payload) {
+            Ok(payload) => (payload.to_string(), None),
+            Err(_) => (STANDARD.encode(payload), Some("base64")),
+        };
+        let letter = DeadLetter {
+            received_at: Utc::now().to_rfc3339(),
+            subject,
+            error: error.to_string(),
+            payload,
+            payload_encoding,
+        };
+        let result = serde_json::to_vec(&letter)
+            .map_err(io::Error::from)
+            .and_then(|mut line| {
+                line.push(b'\n');
+                self.file.write_all(&line)
+            });
+        match result {
+            Ok(()) => self.written += 1,
+            Err(e) => {
+                self.failed += 1;
+                if !self.quiet {
+                    eprintln!(
+                        "⚠️  Failed to write dead letter to {}: {}",
+                        self.path.display(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    /// Dead letters written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Dead letters that could not be written.
+    pub fn failed(&self) -> u64 {
+        self.failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letters(path: &Path) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn utf8_payloads_are_stored_as_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dead.jsonl");
+        let mut file = DeadLetterFile::open(&path, true).unwrap();
+        file.write("stock_prices", b"{bad", &"malformed payload");
+
+        let letters = letters(&path);
+        assert_eq!(letters[0]["payload"], "{bad");
+        assert!(letters[0].get("payload_encoding").is_none());
+        assert_eq!(file.written(), 1);
+    }
+
+    #[test]
+    fn other_payloads_are_stored_as_base64() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dead.jsonl");
+        let mut file = DeadLetterFile::open(&path, true).unwrap();
+        let payload = [0x1f, 0x8b, 0xff, 0x00];
+        file.write("stock_prices.batch", &payload, &"cannot inflate");
+
+        let letters = letters(&path);
+        assert_eq!(letters[0]["payload_encoding"], "base64");
+        let stored = letters[0]["payload"].as_str().unwrap();
+        assert_eq!(STANDARD.decode(stored).unwrap(), payload);
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+/// Entries allocated up front; larger windows grow as IDs arrive.
+const PREALLOCATED: usize = 16 * 1024;
+
+/// Bounded set of recently seen message IDs.
+///
+/// IDs are evicted least-recently-seen first once `capacity` is exceeded,
+/// and expire `ttl` after they were last seen.
+pub struct DedupWindow {
+    capacity: usize,
+    ttl: Duration,
+    seen: HashMap<String, Entry>,
+    /// Recency queue; entries whose generation no longer matches `seen` are stale.
+    order: VecDeque<(String, u64)>,
+    generation: u64,
+}
+
+struct Entry {
+    last_seen: Instant,
+    generation: u64,
+}
+
+impl DedupWindow {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        DedupWindow {
+            capacity,
+            ttl,
+            seen: HashMap::with_capacity(capacity.min(PREALLOCATED)),
+            order: VecDeque::with_capacity(capacity.min(PREALLOCATED)),
+            generation: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    /// Records `id` and returns true if it was not already in the window.
+    pub fn insert(&mut self, id: &str) -> bool {
+        self.insert_at(id, Instant::now())
+    }
+
+    /// Like `insert`, with an explicit clock for deterministic callers.
+    pub fn insert_at(&mut self, id: &str, now: Instant) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+        self.expire(now);
+
+        let is_new = !self.seen.contains_key(id);
+        self.generation += 1;
+        self.seen.insert(
+            id.to_string(),
+            Entry {
+                last_seen: now,
+                generation: self.generation,
+            },
+        );
+        self.order.push_back((id.to_string(), self.generation));
+
+        while self.seen.len() > self.capacity {
+            self.pop_oldest();
+        }
+
+        // Repeated duplicates leave stale queue entries behind; compact them away
+        if self.order.len() > self.capacity.saturating_mul(2) {
+            let seen = &self.seen;
+            self.order.retain(|(id, generation)| {
+                seen.get(id).is_some_and(|e| e.generation == *generation)
+            });
+        }
+
+        is_new
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while let Some((id, generation)) = self.order.front() {
+            match self.seen.get(id) {
+                Some(entry) if entry.generation == *generation => {
+                    if now.duration_since(entry.last_seen) < self.ttl {
+                        break;
+                    }
+                    self.pop_oldest();
+                }
+                _ => {
+                    self.order.pop_front();
+                }
+            }
+        }
+    }
+
+    fn pop_oldest(&mut self) {
+        while let Some((id, generation)) = self.order.pop_front() {
+            if self
+                .seen
+                .get(&id)
+                .is_some_and(|e| e.generation == generation)
+            {
+                self.seen.remove(&id);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn repeated_id_is_a_duplicate() {
+        let mut window = DedupWindow::new(10, TTL);
+        let now = Instant::now();
+        assert!(window.insert_at("a", now));
+        assert!(!window.insert_at("a", now));
+        assert!(window.insert_at("b", now));
+        assert_eq!(window.len(), 2);
+    }
+
+    #[test]
+    fn capacity_evicts_least_recently_seen_first() {
+        let mut window = DedupWindow::new(2, TTL);
+        let now = Instant::now();
+        assert!(window.insert_at("a", now));
+        assert!(window.insert_at("b", now));
+        // Seeing "a" again makes "b" the oldest
+        assert!(!window.insert_at("a", now));
+        assert!(window.insert_at("c", now));
+        assert_eq!(window.len(), 2);
+        assert!(!window.insert_at("a", now));
+        assert!(!window.insert_at("c", now));
+        assert!(window.insert_at("b", now));
+    }
+
+    #[test]
+    fn ids_expire_after_ttl() {
+        let mut window = DedupWindow::new(10, TTL);
+        let start = Instant::now();
+        assert!(window.insert_at("a", start));
+        assert!(window.insert_at("b", start + Duration::from_secs(30)));
+        assert!(window.insert_at("a", start + TTL));
+        assert!(!window.insert_at("b", start + TTL));
+    }
+
+    #[test]
+    fn seeing_an_id_again_extends_its_ttl() {
+        let mut window = DedupWindow::new(10, TTL);
+        let start = Instant::now();
+        assert!(window.insert_at("a", start));
+        assert!(!window.insert_at("a", start + Duration::from_secs(50)));
+        assert!(!window.insert_at("a", start + Duration::from_secs(100)));
+    }
+
+    #[test]
+    fn zero_capacity_disables_deduplication() {
+        let mut window = DedupWindow::new(0, TTL);
+        let now = Instant::now();
+        assert!(window.insert_at("a", now));
+        assert!(window.insert_at("a", now));
+        assert!(window.is_empty());
+    }
+
+    #[test]
+    fn repeated_duplicates_stay_bounded() {
+        let mut window = DedupWindow::new(3, TTL);
+        let now = Instant::now();
+        for _ in 0..100 {
+            window.insert_at("a", now);
+        }
+        assert_eq!(window.len(), 1);
+        assert!(window.order.len() <= 6);
+    }
+
+    #[test]
+    fn huge_capacity_is_allocated_lazily() {
+        let mut window = DedupWindow::new(usize::MAX, TTL);
+        assert!(window.insert("a"));
+        assert!(!window.insert("a"));
+        assert_eq!(window.len(), 1);
+    }
+}
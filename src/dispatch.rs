@@ -0,0 +1,121 @@
+use std::fmt;
+
+use crate::types::{MarketStatus, PayloadError, StockBar, StockPrice};
+use crate::wire::WireFormat;
+
+/// Subjects OHLC bars are published on.
+pub const BARS_SUBJECT: &str = "stock_bars.>";
+
+/// Subject market open/close announcements are published on.
+pub const STATUS_SUBJECT: &str = "market_status";
+
+/// The payload type a subject carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    Price,
+    Bar,
+    Status,
+}
+
+/// A decoded message, typed by the route its subject matched.
+#[derive(Debug, Clone)]
+pub enum MarketMessage {
+    Price(StockPrice),
+    Bar(StockBar),
+    Status(MarketStatus),
+}
+
+#[derive(Debug)]
+pub enum DispatchError {
+    /// No route covers the subject.
+    UnknownSubject(String),
+    Payload(PayloadError),
+}
+
+impl fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DispatchError::UnknownSubject(subject) => write!(f, "unknown subject '{}'", subject),
+            DispatchError::Payload(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DispatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DispatchError::Payload(e) => Some(e),
+            DispatchError::UnknownSubject(_) => None,
+        }
+    }
+}
+
+/// Maps NATS subject patterns to payload types.
+///
+/// Routes are tried in the order they were added; patterns use NATS
+/// wildcards (`*` for one token, `>` for one or more trailing tokens).
+#[derive(Debug, Clone, Default)]
+pub struct Dispatcher {
+    routes: Vec<(String, MessageKind)>,
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The routes every binary understands: prices on `price_subject` and
+    /// below it, bars on `stock_bars.>` and status on `market_status`.
+    pub fn standard(price_subject: &str) -> Self {
+        Dispatcher::new()
+            .route(price_subject, MessageKind::Price)
+            .route(format!("{}.>", price_subject), MessageKind::Price)
+            .route(BARS_SUBJECT, MessageKind::Bar)
+            .route(STATUS_SUBJECT, MessageKind::Status)
+    }
+
+    pub fn route(mut self, pattern: impl Into<String>, kind: MessageKind) -> Self {
+        self.routes.push((pattern.into(), kind));
+        self
+    }
+
+    pub fn kind_of(&self, subject: &str) -> Option<MessageKind> {
+        self.routes
+            .iter()
+            .find(|(pattern, _)| subject_matches(pattern, subject))
+            .map(|(_, kind)| *kind)
+    }
+
+    /// Decodes `payload` as the type routed for `subject`. Prices are also
+    /// validated.
+    pub fn decode(
+        &self,
+        format: WireFormat,
+        subject: &str,
+        payload: &[u8],
+    ) -> Result<MarketMessage, DispatchError> {
+        let kind = self
+            .kind_of(subject)
+            .ok_or_else(|| DispatchError::UnknownSubject(subject.to_string()))?;
+        let message = match kind {
+            MessageKind::Price => format.decode(payload).map(MarketMessage::Price),
+            MessageKind::Bar => format.decode_as(payload).map(MarketMessage::Bar),
+            MessageKind::Status => format.decode_as(payload).map(MarketMessage::Status),
+        };
+        message.map_err(DispatchError::Payload)
+    }
+}
+
+/// Whether `subject` matches a NATS subject `pattern`.
+pub fn subject_matches(pattern: &str, subject: &str) -> bool {
+    let mut subject_tokens = subject.split('.');
+    for token in pattern.split('.') {
+        match (token, subject_tokens.next()) {
+            (">", Some(_)) => return true,
+            ("*", Some(_)) => {}
+            (token, Some(actual)) if token == actual => {}
+            _ => return false,
+        }
+    }
+    subject_tokens.next().is_none()
+}
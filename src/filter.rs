@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+
+use crate::types::StockPrice;
+
+/// Client-side tick filter built from `--symbols`, `--exclude-symbols`,
+/// `--min-price` and `--max-price`.
+///
+/// Rules apply in order of precedence: a symbol on the include list is
+/// kept regardless of the exclude list, and anything else passes only if
+/// no include list is set and it is not excluded. The price range then
+/// applies to every tick still kept. Symbols compare case-insensitively.
+#[derive(Debug, Clone, Default)]
+pub struct TickFilter {
+    symbols: HashSet<String>,
+    exclude: HashSet<String>,
+    min_price: Option<f64>,
+    max_price: Option<f64>,
+}
+
+impl TickFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep only these symbols; an empty list keeps every symbol.
+    pub fn symbols<S: AsRef<str>>(mut self, symbols: impl IntoIterator<Item = S>) -> Self {
+        self.symbols = normalize(symbols);
+        self
+    }
+
+    pub fn exclude<S: AsRef<str>>(mut self, symbols: impl IntoIterator<Item = S>) -> Self {
+        self.exclude = normalize(symbols);
+        self
+    }
+
+    /// Inclusive price bounds.
+    pub fn price_range(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.min_price = min;
+        self.max_price = max;
+        self
+    }
+
+    pub fn accepts(&self, stock: &StockPrice) -> bool {
+        let symbol = stock.symbol.to_ascii_uppercase();
+        let symbol_kept = if self.symbols.is_empty() {
+            !self.exclude.contains(&symbol)
+        } else {
+            self.symbols.contains(&symbol)
+        };
+        symbol_kept
+            && self.min_price.is_none_or(|min| stock.price >= min)
+            && self.max_price.is_none_or(|max| stock.price <= max)
+    }
+}
+
+fn normalize<S: AsRef<str>>(symbols: impl IntoIterator<Item = S>) -> HashSet<String> {
+    symbols
+        .into_iter()
+        .map(|symbol| symbol.as_ref().trim().to_ascii_uppercase())
+        .filter(|symbol| !symbol.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(symbol: &str, price: f64) -> StockPrice {
+        StockPrice {
+            symbol: symbol.to_string(),
+            price,
+            ..StockPrice::default()
+        }
+    }
+
+    #[test]
+    fn empty_filter_accepts_everything() {
+        let filter = TickFilter::new();
+        assert!(filter.accepts(&tick("AAPL", 1.0)));
+        assert!(filter.accepts(&tick("ANY", 0.0)));
+    }
+
+    #[test]
+    fn include_list_keeps_only_its_symbols() {
+        let filter = TickFilter::new().symbols(["aapl", " msft "]);
+        assert!(filter.accepts(&tick("AAPL", 1.0)));
+        assert!(filter.accepts(&tick("msft", 1.0)));
+        assert!(!filter.accepts(&tick("TSLA", 1.0)));
+    }
+
+    #[test]
+    fn exclude_list_drops_its_symbols() {
+        let filter = TickFilter::new().exclude(["TSLA"]);
+        assert!(!filter.accepts(&tick("tsla", 1.0)));
+        assert!(filter.accepts(&tick("AAPL", 1.0)));
+    }
+
+    #[test]
+    fn include_beats_exclude() {
+        let filter = TickFilter::new()
+            .symbols(["AAPL"])
+            .exclude(["AAPL", "TSLA"]);
+        assert!(filter.accepts(&tick("AAPL", 1.0)));
+        assert!(!filter.accepts(&tick("TSLA", 1.0)));
+        assert!(!filter.accepts(&tick("MSFT", 1.0)));
+    }
+
+    #[test]
+    fn price_range_is_inclusive() {
+        let filter = TickFilter::new().price_range(Some(10.0), Some(20.0));
+        assert!(filter.accepts(&tick("AAPL", 10.0)));
+        assert!(filter.accepts(&tick("AAPL", 20.0)));
+        assert!(!filter.accepts(&tick("AAPL", 9.99)));
+        assert!(!filter.accepts(&tick("AAPL", 20.01)));
+    }
+
+    #[test]
+    fn price_range_applies_to_kept_symbols() {
+        let filter = TickFilter::new()
+            .symbols(["AAPL"])
+            .exclude(["AAPL"])
+            .price_range(Some(10.0), None);
+        assert!(filter.accepts(&tick("AAPL", 15.0)));
+        assert!(!filter.accepts(&tick("AAPL", 5.0)));
+    }
+
+    #[test]
+    fn blank_symbols_are_ignored() {
+        let filter = TickFilter::new().symbols(["", "  "]);
+        assert!(filter.accepts(&tick("AAPL", 1.0)));
+    }
+}
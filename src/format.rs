@@ -0,0 +1,418 @@
+use serde_json::Value;
+use std::str::FromStr;
+
+use crate::types::StockPrice;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether colored output should be used, honoring the `NO_COLOR` convention
+/// (https://no-color.org) in addition to an explicit opt-out flag.
+pub fn color_enabled(no_color_flag: bool) -> bool {
+    !no_color_flag && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+/// Formats a received tick, showing the direction, delta, and percent change
+/// relative to the previous price seen for the same symbol.
+///
+/// Rising prices are green with ▲, falling prices red with ▼; unchanged
+/// prices and a symbol's first tick use the default color.
+pub fn format_tick(stock: &StockPrice, previous: Option<f64>, color: bool) -> String {
+    let Some(previous) = previous else {
+        return format!(
+            "📥 Received: {} {:.2} at {}",
+            stock.symbol, stock.price, stock.timestamp
+        );
+    };
+
+    let delta = stock.price - previous;
+    let percent = if previous != 0.0 {
+        delta / previous * 100.0
+    } else {
+        0.0
+    };
+
+    let (arrow, paint) = if delta > 0.0 {
+        ("▲", GREEN)
+    } else if delta < 0.0 {
+        ("▼", RED)
+    } else {
+        ("=", "")
+    };
+
+    let change = format!(
+        "{:.2} {} {:+.2} ({:+.2}%)",
+        stock.price, arrow, delta, percent
+    );
+    let change = if color && !paint.is_empty() {
+        format!("{}{}{}", paint, change, RESET)
+    } else {
+        change
+    };
+
+    format!(
+        "📥 Received: {} {} at {}",
+        stock.symbol, change, stock.timestamp
+    )
+}
+
+/// How the consumer prints each tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable lines with price changes.
+    #[default]
+    Pretty,
+    /// One JSON object per line.
+    Jsonl,
+    /// Comma-separated values after a header row.
+    Csv,
+    /// Aligned columns after a header row.
+    Table,
+}
+
+/// CSV and table columns when no projection is given, in `StockPrice` order.
+pub const DEFAULT_FIELDS: &[&str] = &["symbol", "price", "timestamp"];
+
+/// The fields, in order, that machine-readable output is restricted to.
+///
+/// Fields are named as in the tick's JSON form, such as `publisher_id`.
+/// Nested fields would be dotted paths (`parent.child`), but `StockPrice`
+/// currently has none, so only its top-level fields are accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Projection {
+    fields: Vec<String>,
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection {
+            fields: DEFAULT_FIELDS
+                .iter()
+                .map(|field| field.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl Projection {
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// The selected values of `value`; paths it lacks are `null`.
+    pub fn project(&self, value: &Value) -> Vec<Value> {
+        self.fields
+            .iter()
+            .map(|field| {
+                field
+                    .split('.')
+                    .try_fold(value, |value, key| value.get(key))
+                    .cloned()
+                    .unwrap_or(Value::Null)
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Projection {
+    type Err = String;
+
+    /// Parses a comma-separated list such as `symbol,price`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let valid = valid_fields();
+        let fields: Vec<String> = value
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .map(String::from)
+            .collect();
+        if fields.is_empty() {
+            return Err("no fields given".to_string());
+        }
+        if let Some(unknown) = fields.iter().find(|field| !valid.contains(field)) {
+            return Err(format!(
+                "unknown field '{}'; valid fields are {}",
+                unknown,
+                valid.join(", ")
+            ));
+        }
+        Ok(Projection { fields })
+    }
+}
+
+/// Dotted paths of every leaf in a tick's JSON form.
+fn valid_fields() -> Vec<String> {
+    fn collect(prefix: &str, value: &Value, fields: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    collect(&path, value, fields);
+                }
+            }
+            _ => fields.push(prefix.to_string()),
+        }
+    }
+
+    let blank = StockPrice::default();
+    let mut fields = Vec::new();
+    if let Ok(value) = serde_json::to_value(&blank) {
+        collect("", &value, &mut fields);
+    }
+    fields
+}
+
+/// Smallest table column widths for fields whose size is bounded, so later
+/// rows stay aligned when their values are longer than the first row's: an
+/// RFC 3339 timestamp with nanoseconds and an offset, any price in its
+/// shortest round-trip form (at most 24 characters), or a 10-digit sequence
+/// number.
+const TABLE_WIDTHS: &[(&str, usize)] =
+    &[("symbol", 8), ("price", 24), ("timestamp", 35), ("seq", 10)];
+
+/// Formats ticks in the chosen output format, keeping the state a header
+/// row needs.
+#[derive(Debug, Clone)]
+pub struct TickPrinter {
+    format: OutputFormat,
+    /// Columns for CSV and tables, and the fields of each JSON line.
+    projection: Projection,
+    /// JSON lines carry the whole tick when no projection was given.
+    whole_record: bool,
+    /// Column widths, fixed by the first table row.
+    widths: Option<Vec<usize>>,
+}
+
+impl TickPrinter {
+    /// Without a projection, JSON lines carry every field and CSV and tables
+    /// show `DEFAULT_FIELDS`.
+    pub fn new(format: OutputFormat, projection: Option<Projection>) -> Self {
+        TickPrinter {
+            format,
+            whole_record: projection.is_none(),
+            projection: projection.unwrap_or_default(),
+            widths: None,
+        }
+    }
+
+    /// A line to print before any tick, for formats that have one up front.
+    pub fn header(&self) -> Option<String> {
+        (self.format == OutputFormat::Csv).then(|| {
+            let fields: Vec<&str> = self
+                .projection
+                .fields()
+                .iter()
+                .map(|f| f.as_str())
+                .collect();
+            csv_row(&fields)
+        })
+    }
+
+    /// The output for one tick; the first table row also carries the header.
+    pub fn line(&mut self, stock: &StockPrice, previous: Option<f64>, color: bool) -> String {
+        if self.format == OutputFormat::Pretty {
+            return format_tick(stock, previous, color);
+        }
+
+        if self.format == OutputFormat::Jsonl && self.whole_record {
+            return serde_json::to_string(stock).unwrap_or_default();
+        }
+
+        let value = serde_json::to_value(stock).unwrap_or(Value::Null);
+        let values = self.projection.project(&value);
+        match self.format {
+            OutputFormat::Jsonl => {
+                // Built by hand so keys keep the requested order
+                let pairs: Vec<String> = self
+                    .projection
+                    .fields()
+                    .iter()
+                    .zip(&values)
+                    .map(|(field, value)| format!("{}:{}", Value::from(field.as_str()), value))
+                    .collect();
+                format!("{{{}}}", pairs.join(","))
+            }
+            OutputFormat::Csv => {
+                let cells: Vec<String> = values.iter().map(cell).collect();
+                csv_row(&cells)
+            }
+            _ => {
+                let cells: Vec<String> = values.iter().map(cell).collect();
+                match self.widths.as_ref() {
+                    Some(widths) => table_row(&cells, widths),
+                    None => {
+                        let fields = self.projection.fields();
+                        let widths: Vec<usize> = fields
+                            .iter()
+                            .zip(&cells)
+                            .map(|(field, cell)| {
+                                let declared = TABLE_WIDTHS
+                                    .iter()
+                                    .find(|(name, _)| name == field)
+                                    .map_or(0, |(_, width)| *width);
+                                field.len().max(cell.len()).max(declared)
+                            })
+                            .collect();
+                        let header = table_row(fields, &widths);
+                        let row = table_row(&cells, &widths);
+                        self.widths = Some(widths);
+                        format!("{}\n{}", header, row)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A value as plain text: strings unquoted, `null` empty.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn csv_row(cells: &[impl AsRef<str>]) -> String {
+    cells
+        .iter()
+        .map(|cell| {
+            let cell = cell.as_ref();
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn table_row(cells: &[impl AsRef<str>], widths: &[usize]) -> String {
+    cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{:<width$}", cell.as_ref(), width = width))
+        .collect::<Vec<_>>()
+        .join("  ")
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(price: f64) -> StockPrice {
+        StockPrice {
+            symbol: "AAPL".to_string(),
+            price,
+            timestamp: "2026-10-14T10:00:00Z".to_string(),
+            ..StockPrice::default()
+        }
+    }
+
+    #[test]
+    fn first_tick_has_no_change() {
+        assert_eq!(
+            format_tick(&tick(100.0), None, false),
+            "📥 Received: AAPL 100.00 at 2026-10-14T10:00:00Z"
+        );
+    }
+
+    #[test]
+    fn rising_price() {
+        assert_eq!(
+            format_tick(&tick(110.0), Some(100.0), false),
+            "📥 Received: AAPL 110.00 ▲ +10.00 (+10.00%) at 2026-10-14T10:00:00Z"
+        );
+    }
+
+    #[test]
+    fn falling_price() {
+        assert_eq!(
+            format_tick(&tick(90.0), Some(100.0), false),
+            "📥 Received: AAPL 90.00 ▼ -10.00 (-10.00%) at 2026-10-14T10:00:00Z"
+        );
+    }
+
+    #[test]
+    fn unchanged_price() {
+        assert_eq!(
+            format_tick(&tick(100.0), Some(100.0), false),
+            "📥 Received: AAPL 100.00 = +0.00 (+0.00%) at 2026-10-14T10:00:00Z"
+        );
+    }
+
+    #[test]
+    fn previous_zero_does_not_divide_by_zero() {
+        assert_eq!(
+            format_tick(&tick(5.0), Some(0.0), false),
+            "📥 Received: AAPL 5.00 ▲ +5.00 (+0.00%) at 2026-10-14T10:00:00Z"
+        );
+    }
+
+    #[test]
+    fn color_wraps_only_the_change() {
+        assert_eq!(
+            format_tick(&tick(110.0), Some(100.0), true),
+            "📥 Received: AAPL \x1b[32m110.00 ▲ +10.00 (+10.00%)\x1b[0m at 2026-10-14T10:00:00Z"
+        );
+        assert!(!format_tick(&tick(100.0), Some(100.0), true).contains('\x1b'));
+    }
+
+    #[test]
+    fn projection_parses_known_fields_in_order() {
+        let projection: Projection = " price, publisher_id ,,symbol".parse().unwrap();
+        assert_eq!(projection.fields(), ["price", "publisher_id", "symbol"]);
+        let value = serde_json::to_value(tick(1.5)).unwrap();
+        assert_eq!(
+            projection.project(&value),
+            [Value::from(1.5), Value::from(""), Value::from("AAPL")]
+        );
+    }
+
+    #[test]
+    fn projection_rejects_unknown_fields() {
+        let error = "symbol,meta.producer_id".parse::<Projection>().unwrap_err();
+        assert_eq!(
+            error,
+            "unknown field 'meta.producer_id'; valid fields are price, publisher_id, seq, symbol, timestamp"
+        );
+        assert_eq!(" , ".parse::<Projection>().unwrap_err(), "no fields given");
+    }
+
+    #[test]
+    fn jsonl_without_fields_keeps_the_whole_tick() {
+        let mut printer = TickPrinter::new(OutputFormat::Jsonl, None);
+        let line = printer.line(&tick(1.5), None, false);
+        assert_eq!(line, serde_json::to_string(&tick(1.5)).unwrap());
+        assert!(line.contains("publisher_id"));
+    }
+
+    #[test]
+    fn jsonl_with_fields_keeps_their_order() {
+        let projection = "price,symbol".parse().unwrap();
+        let mut printer = TickPrinter::new(OutputFormat::Jsonl, Some(projection));
+        assert_eq!(
+            printer.line(&tick(1.5), None, false),
+            r#"{"price":1.5,"symbol":"AAPL"}"#
+        );
+    }
+
+    #[test]
+    fn table_rows_stay_aligned_as_prices_grow() {
+        let mut printer = TickPrinter::new(OutputFormat::Table, None);
+        let first = printer.line(&tick(1.5), None, false);
+        let later = printer.line(&tick(123456.75), None, false);
+        let (header, first) = first.split_once('\n').unwrap();
+        let column = |line: &str| line.find("2026").unwrap();
+        assert_eq!(column(first), header.find("timestamp").unwrap());
+        assert_eq!(column(&later), column(first));
+    }
+}
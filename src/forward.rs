@@ -0,0 +1,290 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tokio::{
+    sync::mpsc,
+    task::JoinHandle,
+    time::{Duration, Instant, sleep},
+};
+
+use crate::types::StockPrice;
+
+/// Ticks buffered between the receive loop and the forwarding task.
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1000;
+
+/// Retries after the first failed POST of a batch.
+const MAX_RETRIES: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Consecutive failed batches that open the circuit breaker.
+const FAILURE_THRESHOLD: u32 = 5;
+
+/// How long the breaker stays open before a batch is tried again.
+const COOLDOWN: Duration = Duration::from_secs(30);
+
+/// An extra request header given as `"Name: value"`.
+#[derive(Debug, Clone)]
+pub struct ForwardHeader {
+    pub name: HeaderName,
+    pub value: HeaderValue,
+}
+
+impl FromStr for ForwardHeader {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = s
+            .split_once(':')
+            .ok_or_else(|| format!("'{}' is not of the form \"Name: value\"", s))?;
+        Ok(ForwardHeader {
+            name: HeaderName::from_str(name.trim()).map_err(|e| e.to_string())?,
+            value: HeaderValue::from_str(value.trim()).map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ForwardConfig {
+    pub url: String,
+    /// Ticks per POST. With 1 each tick is sent as an object, otherwise as an array.
+    pub batch_size: usize,
+    pub queue_capacity: usize,
+    pub headers: Vec<ForwardHeader>,
+    /// Suppress progress and failure messages, e.g. while a TUI owns the
+    /// terminal; the totals still count every failure.
+    pub quiet: bool,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    sent: AtomicU64,
+    dropped: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// Totals reported when forwarding stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForwardStats {
+    /// Ticks accepted by the webhook.
+    pub sent: u64,
+    /// Ticks dropped because the queue was full or the breaker was open.
+    pub dropped: u64,
+    /// Ticks given up on after every retry failed.
+    pub failed: u64,
+}
+
+impl fmt::Display for ForwardStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} sent | {} dropped | {} failed",
+            self.sent, self.dropped, self.failed
+        )
+    }
+}
+
+/// Forwards ticks to an HTTP endpoint from a background task.
+///
+/// `forward` never waits on the network: ticks go through a bounded queue
+/// and are dropped when it is full. After repeated failed batches the task
+/// stops posting for a cooldown and drops what it receives meanwhile.
+pub struct Forwarder {
+    tx: mpsc::Sender<StockPrice>,
+    counters: Arc<Counters>,
+    task: JoinHandle<()>,
+}
+
+impl Forwarder {
+    pub fn spawn(config: ForwardConfig, http: reqwest::Client) -> Self {
+        let (tx, rx) = mpsc::channel(config.queue_capacity.max(1));
+        let counters = Arc::new(Counters::default());
+        let task = tokio::spawn(forward_loop(config, http, rx, counters.clone()));
+        Forwarder { tx, counters, task }
+    }
+
+    /// Queues a tick, dropping it if the queue is full.
+    pub fn forward(&self, stock: StockPrice) {
+        if self.tx.try_send(stock).is_err() {
+            self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn stats(&self) -> ForwardStats {
+        Forwarder::stats_of(&self.counters)
+    }
+
+    /// A handle for reading the totals from another task, e.g. a metrics
+    /// endpoint.
+    pub fn stats_handle(&self) -> ForwardStatsHandle {
+        ForwardStatsHandle(self.counters.clone())
+    }
+
+    /// Stops accepting ticks, waits for the queue to drain and returns the totals.
+    pub async fn finish(self) -> ForwardStats {
+        let Forwarder { tx, counters, task } = self;
+        drop(tx);
+        if let Err(e) = task.await {
+            eprintln!("❌ Webhook forwarding task panicked: {}", e);
+        }
+        Forwarder::stats_of(&counters)
+    }
+
+    fn stats_of(counters: &Counters) -> ForwardStats {
+        ForwardStats {
+            sent: counters.sent.load(Ordering::Relaxed),
+            dropped: counters.dropped.load(Ordering::Relaxed),
+            failed: counters.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Reads a forwarder's running totals; see `Forwarder::stats_handle`.
+#[derive(Debug, Clone)]
+pub struct ForwardStatsHandle(Arc<Counters>);
+
+impl ForwardStatsHandle {
+    pub fn get(&self) -> ForwardStats {
+        Forwarder::stats_of(&self.0)
+    }
+}
+
+enum Outcome {
+    Sent,
+    /// The endpoint refused the batch (4xx); retrying would not help.
+    Rejected,
+    /// Network errors or 5xx responses on every attempt.
+    Failed,
+}
+
+async fn forward_loop(
+    config: ForwardConfig,
+    http: reqwest::Client,
+    mut rx: mpsc::Receiver<StockPrice>,
+    counters: Arc<Counters>,
+) {
+    let headers: HeaderMap = config
+        .headers
+        .iter()
+        .map(|h| (h.name.clone(), h.value.clone()))
+        .collect();
+    let batch_size = config.batch_size.max(1);
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut consecutive_failures = 0;
+    let mut open_until: Option<Instant> = None;
+
+    while rx.recv_many(&mut batch, batch_size).await > 0 {
+        let rows = batch.len() as u64;
+
+        if let Some(until) = open_until {
+            if Instant::now() < until {
+                counters.dropped.fetch_add(rows, Ordering::Relaxed);
+                batch.clear();
+                continue;
+            }
+            // Half-open: this batch decides whether the breaker closes again
+            open_until = None;
+        }
+
+        let body = if batch_size == 1 {
+            serde_json::to_value(&batch[0])
+        } else {
+            serde_json::to_value(&batch)
+        };
+        let body = match body {
+            Ok(body) => body,
+            Err(e) => {
+                if !config.quiet {
+                    eprintln!("❌ Failed to serialize forwarded ticks: {}", e);
+                }
+                counters.failed.fetch_add(rows, Ordering::Relaxed);
+                batch.clear();
+                continue;
+            }
+        };
+
+        match post(&http, &config.url, &headers, &body, config.quiet).await {
+            Outcome::Sent => {
+                if consecutive_failures >= FAILURE_THRESHOLD && !config.quiet {
+                    eprintln!("✅ Webhook recovered, resuming forwarding");
+                }
+                consecutive_failures = 0;
+                counters.sent.fetch_add(rows, Ordering::Relaxed);
+            }
+            Outcome::Rejected => {
+                counters.failed.fetch_add(rows, Ordering::Relaxed);
+            }
+            Outcome::Failed => {
+                counters.failed.fetch_add(rows, Ordering::Relaxed);
+                consecutive_failures += 1;
+                if consecutive_failures >= FAILURE_THRESHOLD {
+                    if !config.quiet {
+                        eprintln!(
+                            "🚫 Webhook failed {} batches in a row; pausing forwarding for {}s",
+                            consecutive_failures,
+                            COOLDOWN.as_secs()
+                        );
+                    }
+                    open_until = Some(Instant::now() + COOLDOWN);
+                }
+            }
+        }
+        batch.clear();
+    }
+}
+
+async fn post(
+    http: &reqwest::Client,
+    url: &str,
+    headers: &HeaderMap,
+    body: &serde_json::Value,
+    quiet: bool,
+) -> Outcome {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        let result = http
+            .post(url)
+            .headers(headers.clone())
+            .json(body)
+            .send()
+            .await;
+
+        match result {
+            Ok(response) if response.status().is_success() => return Outcome::Sent,
+            Ok(response) if response.status().is_client_error() => {
+                if !quiet {
+                    eprintln!("❌ Webhook rejected forwarded ticks: {}", response.status());
+                }
+                return Outcome::Rejected;
+            }
+            Ok(response) if attempt > MAX_RETRIES => {
+                if !quiet {
+                    eprintln!(
+                        "❌ Webhook failed after {} attempts: {}",
+                        attempt,
+                        response.status()
+                    );
+                }
+                return Outcome::Failed;
+            }
+            Err(e) if attempt > MAX_RETRIES => {
+                if !quiet {
+                    eprintln!("❌ Webhook failed after {} attempts: {}", attempt, e);
+                }
+                return Outcome::Failed;
+            }
+            _ => {}
+        }
+
+        sleep(backoff).await;
+        backoff *= 2;
+    }
+}
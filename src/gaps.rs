@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+/// Messages missing between two ticks from the same publisher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gap {
+    pub publisher_id: String,
+    /// The sequence number that should have come next.
+    pub expected: u64,
+    pub received: u64,
+}
+
+impl Gap {
+    pub fn missed(&self) -> u64 {
+        self.received - self.expected
+    }
+}
+
+/// Tracks the last sequence number seen per publisher to spot dropped
+/// messages.
+///
+/// Ticks without a publisher ID or sequence number are ignored. A sequence
+/// number at or below the last one is taken as a publisher restart or a
+/// redelivery and simply becomes the new baseline.
+#[derive(Debug, Default)]
+pub struct GapDetector {
+    last_seq: HashMap<String, u64>,
+}
+
+impl GapDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn observe(&mut self, publisher_id: &str, seq: u64) -> Option<Gap> {
+        if publisher_id.is_empty() || seq == 0 {
+            return None;
+        }
+        let last = self.last_seq.insert(publisher_id.to_string(), seq)?;
+        let expected = last + 1;
+        (seq > expected).then(|| Gap {
+            publisher_id: publisher_id.to_string(),
+            expected,
+            received: seq,
+        })
+    }
+}
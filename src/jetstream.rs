@@ -0,0 +1,162 @@
+use async_nats::jetstream::{
+    self,
+    consumer::{AckPolicy, DeliverPolicy, pull},
+    context::{GetStreamByNameErrorKind, RequestError, RequestErrorKind},
+};
+use chrono::{DateTime, Utc};
+use std::{fmt, str::FromStr};
+
+/// Where a newly created durable consumer starts reading its stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayFrom {
+    /// A stream sequence number.
+    Sequence(u64),
+    /// The first message stored at or after this time.
+    Time(DateTime<Utc>),
+}
+
+impl FromStr for ReplayFrom {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Ok(sequence) = value.parse::<u64>() {
+            return Ok(ReplayFrom::Sequence(sequence));
+        }
+        DateTime::parse_from_rfc3339(value)
+            .map(|time| ReplayFrom::Time(time.with_timezone(&Utc)))
+            .map_err(|_| {
+                format!(
+                    "'{}' is neither a stream sequence number nor an RFC 3339 timestamp",
+                    value
+                )
+            })
+    }
+}
+
+impl ReplayFrom {
+    fn deliver_policy(self) -> Result<DeliverPolicy, JetStreamError> {
+        match self {
+            ReplayFrom::Sequence(start_sequence) => {
+                Ok(DeliverPolicy::ByStartSequence { start_sequence })
+            }
+            ReplayFrom::Time(time) => {
+                let nanos = time.timestamp_nanos_opt().ok_or_else(|| {
+                    JetStreamError::Setup(format!("replay time {} is out of range", time))
+                })?;
+                let start_time = time::OffsetDateTime::from_unix_timestamp_nanos(nanos.into())
+                    .map_err(|e| JetStreamError::Setup(e.to_string()))?;
+                Ok(DeliverPolicy::ByStartTime { start_time })
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum JetStreamError {
+    /// The server answered with "no responders": JetStream is not enabled.
+    Unavailable,
+    /// No stream captures the subscribed subject.
+    NoStream {
+        subject: String,
+    },
+    Setup(String),
+}
+
+impl fmt::Display for JetStreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JetStreamError::Unavailable => write!(
+                f,
+                "JetStream is not available on this NATS server (no responders); start nats-server with -js"
+            ),
+            JetStreamError::NoStream { subject } => write!(
+                f,
+                "no JetStream stream captures subject '{}'; create one first, e.g. `nats stream add --subjects '{}'`",
+                subject, subject
+            ),
+            JetStreamError::Setup(message) => write!(f, "JetStream setup failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for JetStreamError {}
+
+/// Binds (creating if needed) a durable pull consumer named `durable` on the
+/// stream that captures `subject`, and returns its message stream.
+///
+/// The server tracks the ack floor, so a restarted consumer resumes after the
+/// last acknowledged message. `replay_from` only takes effect when the durable
+/// consumer is first created.
+pub async fn durable_messages(
+    client: async_nats::Client,
+    subject: &str,
+    stream_name: Option<&str>,
+    durable: &str,
+    replay_from: Option<ReplayFrom>,
+) -> Result<pull::Stream, JetStreamError> {
+    let context = jetstream::new(client);
+
+    let stream_name = match stream_name {
+        Some(name) => name.to_string(),
+        None => context
+            .stream_by_subject(subject)
+            .await
+            .map_err(|e| match e.kind() {
+                GetStreamByNameErrorKind::NotFound => JetStreamError::NoStream {
+                    subject: subject.to_string(),
+                },
+                GetStreamByNameErrorKind::Request if is_no_responders(&e) => {
+                    JetStreamError::Unavailable
+                }
+                _ => JetStreamError::Setup(e.to_string()),
+            })?,
+    };
+
+    let stream = context
+        .get_stream(&stream_name)
+        .await
+        .map_err(|e| JetStreamError::Setup(format!("stream '{}': {}", stream_name, e)))?;
+
+    let deliver_policy = match replay_from {
+        Some(replay_from) => replay_from.deliver_policy()?,
+        None => DeliverPolicy::All,
+    };
+
+    let consumer: jetstream::consumer::Consumer<pull::Config> = stream
+        .get_or_create_consumer(
+            durable,
+            pull::Config {
+                durable_name: Some(durable.to_string()),
+                filter_subject: subject.to_string(),
+                deliver_policy,
+                ack_policy: AckPolicy::Explicit,
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|e| JetStreamError::Setup(format!("consumer '{}': {}", durable, e)))?;
+
+    consumer
+        .messages()
+        .await
+        .map_err(|e| JetStreamError::Setup(e.to_string()))
+}
+
+/// Walks an error's source chain looking for a "no responders" reply.
+fn is_no_responders(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(request) = error.downcast_ref::<RequestError>()
+            && matches!(request.kind(), RequestErrorKind::NoResponders)
+        {
+            return true;
+        }
+        if let Some(request) = error.downcast_ref::<async_nats::RequestError>()
+            && matches!(request.kind(), async_nats::RequestErrorKind::NoResponders)
+        {
+            return true;
+        }
+        current = error.source();
+    }
+    false
+}
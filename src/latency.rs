@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use hdrhistogram::Histogram;
+use std::fmt;
+
+/// Largest latency tracked precisely; anything above is saturated to this value.
+const MAX_TRACKED_MICROS: u64 = 60 * 60 * 1_000_000;
+
+/// Streaming end-to-end latency histogram with microsecond resolution.
+///
+/// Negative latencies (the sender's clock is ahead of ours) are counted
+/// separately instead of being recorded, so clock skew cannot corrupt the
+/// percentiles.
+pub struct LatencyHistogram {
+    histogram: Histogram<u64>,
+    negative: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        LatencyHistogram {
+            histogram: Histogram::new_with_bounds(1, MAX_TRACKED_MICROS, 3)
+                .expect("valid histogram bounds"),
+            negative: 0,
+        }
+    }
+
+    /// Records the latency between `sent` and `received`.
+    pub fn record(&mut self, sent: DateTime<Utc>, received: DateTime<Utc>) {
+        match (received - sent).num_microseconds() {
+            Some(micros) if micros < 0 => self.negative += 1,
+            Some(micros) => self.histogram.saturating_record(micros as u64),
+            None => self.histogram.saturating_record(MAX_TRACKED_MICROS),
+        }
+    }
+
+    /// Parses an RFC 3339 publish timestamp and records its latency relative
+    /// to now. Returns false when the timestamp cannot be parsed.
+    pub fn record_since(&mut self, timestamp: &str) -> bool {
+        match DateTime::parse_from_rfc3339(timestamp) {
+            Ok(sent) => {
+                self.record(sent.with_timezone(&Utc), Utc::now());
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.histogram.len()
+    }
+
+    pub fn negative(&self) -> u64 {
+        self.negative
+    }
+
+    /// Latency at the given percentile (0.0..=100.0) in milliseconds.
+    pub fn percentile_ms(&self, percentile: f64) -> f64 {
+        self.histogram.value_at_percentile(percentile) as f64 / 1000.0
+    }
+
+    pub fn max_ms(&self) -> f64 {
+        self.histogram.max() as f64 / 1000.0
+    }
+}
+
+impl fmt::Display for LatencyHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} p50={:.2}ms p95={:.2}ms p99={:.2}ms max={:.2}ms negative={}",
+            self.count(),
+            self.percentile_ms(50.0),
+            self.percentile_ms(95.0),
+            self.percentile_ms(99.0),
+            self.max_ms(),
+            self.negative
+        )
+    }
+}
@@ -1 +1,30 @@
+pub mod alerts;
+#[cfg(feature = "alpha-vantage")]
+pub mod alpha_vantage;
+pub mod auth_client;
+pub mod auth_service;
+pub mod batch;
+pub mod board;
+pub mod config;
+pub mod counters;
+pub mod dead_letter;
+pub mod dedup;
+pub mod dispatch;
+pub mod filter;
+pub mod format;
+pub mod forward;
+pub mod gaps;
+pub mod jetstream;
+pub mod latency;
+pub mod metrics;
+pub mod nats;
+pub mod price_source;
+pub mod quote;
+pub mod recorder;
+pub mod shutdown;
+pub mod stats;
+pub mod store;
+pub mod subscriber;
 pub mod types;
+pub mod wire;
+pub mod ws;
@@ -0,0 +1,128 @@
+use std::fmt::Write;
+
+// Metric and label names, shared so every service exposes the same ones.
+
+pub const MESSAGES_RECEIVED_TOTAL: &str = "messages_received_total";
+pub const PARSE_FAILURES_TOTAL: &str = "parse_failures_total";
+pub const DUPLICATES_DROPPED_TOTAL: &str = "duplicates_dropped_total";
+pub const RECONNECTS_TOTAL: &str = "reconnects_total";
+pub const END_TO_END_LATENCY_SECONDS: &str = "end_to_end_latency_seconds";
+pub const FORWARD_SENT_TOTAL: &str = "forward_sent_total";
+pub const FORWARD_DROPPED_TOTAL: &str = "forward_dropped_total";
+pub const FORWARD_FAILED_TOTAL: &str = "forward_failed_total";
+pub const ALERT_WEBHOOK_FAILURES_TOTAL: &str = "alert_webhook_failures_total";
+
+pub const SYMBOL_LABEL: &str = "symbol";
+pub const SUBJECT_LABEL: &str = "subject";
+
+/// Upper bounds, in seconds, of the end-to-end latency histogram.
+pub const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+/// A Prometheus-style histogram with fixed bucket bounds.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    bounds: Vec<f64>,
+    /// Observations per bucket, not cumulative; the last is `+Inf`.
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &[f64]) -> Self {
+        Histogram {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len() + 1],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn observe(&mut self, value: f64) {
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.counts[bucket] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// Builds a scrape response in the Prometheus text exposition format.
+#[derive(Debug, Default)]
+pub struct MetricsWriter {
+    out: String,
+}
+
+impl MetricsWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn counter(&mut self, name: &str, help: &str, value: u64) {
+        self.labelled_counter(name, help, [(&[][..], value)]);
+    }
+
+    /// A counter family with one sample per label set.
+    pub fn labelled_counter<'a>(
+        &mut self,
+        name: &str,
+        help: &str,
+        samples: impl IntoIterator<Item = (&'a [(&'a str, &'a str)], u64)>,
+    ) {
+        self.family(name, help, "counter");
+        for (labels, value) in samples {
+            let _ = writeln!(self.out, "{}{} {}", name, format_labels(labels), value);
+        }
+    }
+
+    pub fn histogram(&mut self, name: &str, help: &str, histogram: &Histogram) {
+        self.family(name, help, "histogram");
+        let mut cumulative = 0;
+        for (bound, count) in histogram.bounds.iter().zip(&histogram.counts) {
+            cumulative += count;
+            let _ = writeln!(
+                self.out,
+                "{}_bucket{{le=\"{}\"}} {}",
+                name, bound, cumulative
+            );
+        }
+        let _ = writeln!(
+            self.out,
+            "{}_bucket{{le=\"+Inf\"}} {}",
+            name, histogram.count
+        );
+        let _ = writeln!(self.out, "{}_sum {}", name, histogram.sum);
+        let _ = writeln!(self.out, "{}_count {}", name, histogram.count);
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+
+    fn family(&mut self, name: &str, help: &str, kind: &str) {
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+    }
+}
+
+fn format_labels(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect();
+    format!("{{{}}}", labels.join(","))
+}
@@ -0,0 +1,350 @@
+use async_nats::{ConnectErrorKind, ConnectOptions};
+use std::{env, ffi::OsString, fmt, path::PathBuf};
+
+use crate::config::{NATS_URL_ENV, env_value};
+
+/// Address of the local NATS server every binary connects to.
+pub const NATS_URL: &str = "nats://127.0.0.1:4222";
+
+/// Subject ticks are published on unless `NATS_SUBJECT` overrides it.
+pub const DEFAULT_SUBJECT: &str = "stock_prices";
+/// Overrides the tick subject, e.g. to keep environments sharing a cluster apart.
+pub const SUBJECT_ENV: &str = "NATS_SUBJECT";
+
+/// PEM file with the CA certificates used to verify the server.
+pub const TLS_CA_ENV: &str = "NATS_TLS_CA";
+/// PEM client certificate for mutual TLS; requires `NATS_TLS_KEY`.
+pub const TLS_CERT_ENV: &str = "NATS_TLS_CERT";
+/// PEM private key matching `NATS_TLS_CERT`.
+pub const TLS_KEY_ENV: &str = "NATS_TLS_KEY";
+
+/// Token authentication; takes precedence over user and password.
+pub const TOKEN_ENV: &str = "NATS_TOKEN";
+/// Username for user/password authentication; requires `NATS_PASS`.
+pub const USER_ENV: &str = "NATS_USER";
+/// Password matching `NATS_USER`.
+pub const PASS_ENV: &str = "NATS_PASS";
+
+#[derive(Debug)]
+pub enum ConnectError {
+    /// A TLS variable points at a file that does not exist.
+    MissingFile {
+        var: &'static str,
+        path: PathBuf,
+    },
+    /// Only one of the client certificate and key was set.
+    IncompleteClientCert,
+    /// Only one of the username and password was set.
+    IncompleteUserPassword,
+    /// The server rejected the configured credentials.
+    Rejected(async_nats::ConnectError),
+    Connect(async_nats::ConnectError),
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectError::MissingFile { var, path } => {
+                write!(
+                    f,
+                    "{} points at {}, which does not exist",
+                    var,
+                    path.display()
+                )
+            }
+            ConnectError::IncompleteClientCert => write!(
+                f,
+                "{} and {} must be set together",
+                TLS_CERT_ENV, TLS_KEY_ENV
+            ),
+            ConnectError::IncompleteUserPassword => {
+                write!(f, "{} and {} must be set together", USER_ENV, PASS_ENV)
+            }
+            ConnectError::Rejected(e) => write!(
+                f,
+                "NATS rejected the connection ({}); check {} or {}/{}",
+                e, TOKEN_ENV, USER_ENV, PASS_ENV
+            ),
+            ConnectError::Connect(e) => write!(f, "failed to connect to NATS: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConnectError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConnectError::Connect(e) | ConnectError::Rejected(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// TLS files named by the `NATS_TLS_*` variables.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsSettings {
+    /// CA certificates used to verify the server.
+    pub ca: Option<PathBuf>,
+    /// Client certificate and key for mutual TLS.
+    pub client_cert: Option<(PathBuf, PathBuf)>,
+}
+
+/// How the client authenticates; secrets are kept out of `Debug`.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub enum Auth {
+    #[default]
+    Anonymous,
+    Token(String),
+    UserPassword {
+        user: String,
+        pass: String,
+    },
+}
+
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Auth::Anonymous => write!(f, "Anonymous"),
+            Auth::Token(_) => write!(f, "Token(..)"),
+            Auth::UserPassword { user, .. } => write!(f, "UserPassword({}, ..)", user),
+        }
+    }
+}
+
+/// Connection settings read from the environment, before they are turned
+/// into `ConnectOptions`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectSettings {
+    /// Set when any TLS variable is, which makes TLS required.
+    pub tls: Option<TlsSettings>,
+    pub auth: Auth,
+}
+
+impl ConnectSettings {
+    /// Reads the settings from the environment.
+    ///
+    /// With none of the TLS variables set the connection is plaintext. Setting
+    /// any of them requires TLS. `NATS_TOKEN` is used when set, even if
+    /// `NATS_USER`/`NATS_PASS` are also present; with neither the connection is
+    /// anonymous.
+    pub fn from_env() -> Result<Self, ConnectError> {
+        Self::from_vars(|var| env::var_os(var))
+    }
+
+    /// `from_env` with the variables looked up through `var`.
+    fn from_vars(var: impl Fn(&str) -> Option<OsString>) -> Result<Self, ConnectError> {
+        let ca = file_path(TLS_CA_ENV, var(TLS_CA_ENV))?;
+        let cert = file_path(TLS_CERT_ENV, var(TLS_CERT_ENV))?;
+        let key = file_path(TLS_KEY_ENV, var(TLS_KEY_ENV))?;
+
+        let client_cert = match (cert, key) {
+            (Some(cert), Some(key)) => Some((cert, key)),
+            (None, None) => None,
+            _ => return Err(ConnectError::IncompleteClientCert),
+        };
+        let tls =
+            (ca.is_some() || client_cert.is_some()).then_some(TlsSettings { ca, client_cert });
+
+        let value = |name| {
+            var(name)
+                .and_then(|value| value.into_string().ok())
+                .filter(|value| !value.is_empty())
+        };
+        let auth = match (value(TOKEN_ENV), value(USER_ENV), value(PASS_ENV)) {
+            (Some(token), _, _) => Auth::Token(token),
+            (None, Some(user), Some(pass)) => Auth::UserPassword { user, pass },
+            (None, None, None) => Auth::Anonymous,
+            _ => return Err(ConnectError::IncompleteUserPassword),
+        };
+
+        Ok(ConnectSettings { tls, auth })
+    }
+
+    /// Connect options that apply these settings.
+    pub fn options(&self) -> ConnectOptions {
+        let mut options = ConnectOptions::new();
+        if let Some(tls) = &self.tls {
+            options = options.require_tls(true);
+            if let Some(ca) = &tls.ca {
+                options = options.add_root_certificates(ca.clone());
+            }
+            if let Some((cert, key)) = &tls.client_cert {
+                options = options.add_client_certificate(cert.clone(), key.clone());
+            }
+        }
+        match &self.auth {
+            Auth::Anonymous => options,
+            Auth::Token(token) => options.token(token.clone()),
+            Auth::UserPassword { user, pass } => {
+                options.user_and_password(user.clone(), pass.clone())
+            }
+        }
+    }
+}
+
+/// Builds connect options from the environment; see `ConnectSettings::from_env`.
+pub fn connect_options() -> Result<ConnectOptions, ConnectError> {
+    ConnectSettings::from_env().map(|settings| settings.options())
+}
+
+/// The server to connect to: `NATS_URL`, or the local server.
+pub fn url() -> String {
+    env_value(NATS_URL_ENV).unwrap_or_else(|| NATS_URL.to_string())
+}
+
+/// The subject ticks are published on: `NATS_SUBJECT`, or `stock_prices`.
+pub fn subject() -> String {
+    env_value(SUBJECT_ENV).unwrap_or_else(|| DEFAULT_SUBJECT.to_string())
+}
+
+/// Connects to `url` using the options from `connect_options`.
+pub async fn connect(url: &str) -> Result<async_nats::Client, ConnectError> {
+    connect_options()?
+        .connect(url)
+        .await
+        .map_err(|e| match e.kind() {
+            ConnectErrorKind::AuthorizationViolation | ConnectErrorKind::Authentication => {
+                ConnectError::Rejected(e)
+            }
+            _ => ConnectError::Connect(e),
+        })
+}
+
+/// Checks that a TLS variable set to `value` names an existing file.
+fn file_path(var: &'static str, value: Option<OsString>) -> Result<Option<PathBuf>, ConnectError> {
+    match value {
+        Some(value) if !value.is_empty() => {
+            let path = PathBuf::from(value);
+            if !path.is_file() {
+                return Err(ConnectError::MissingFile { var, path });
+            }
+            Ok(Some(path))
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn settings(vars: &[(&str, &str)]) -> Result<ConnectSettings, ConnectError> {
+        let vars: HashMap<&str, OsString> = vars
+            .iter()
+            .map(|(name, value)| (*name, OsString::from(value)))
+            .collect();
+        ConnectSettings::from_vars(|var| vars.get(var).cloned())
+    }
+
+    /// Creates `names` in a temporary directory, returning their paths.
+    fn files(names: &[&str]) -> (tempfile::TempDir, Vec<String>) {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = names
+            .iter()
+            .map(|name| {
+                let path = dir.path().join(name);
+                std::fs::write(&path, "").unwrap();
+                path.to_str().unwrap().to_string()
+            })
+            .collect();
+        (dir, paths)
+    }
+
+    #[test]
+    fn plaintext_without_tls_variables() {
+        assert_eq!(settings(&[]).unwrap(), ConnectSettings::default());
+    }
+
+    #[test]
+    fn ca_requires_tls() {
+        let (_dir, paths) = files(&["ca.pem"]);
+        let tls = settings(&[(TLS_CA_ENV, &paths[0])]).unwrap().tls;
+        assert_eq!(
+            tls,
+            Some(TlsSettings {
+                ca: Some(PathBuf::from(&paths[0])),
+                client_cert: None,
+            })
+        );
+    }
+
+    #[test]
+    fn client_certificate_is_applied() {
+        let (_dir, paths) = files(&["cert.pem", "key.pem"]);
+        let tls = settings(&[(TLS_CERT_ENV, &paths[0]), (TLS_KEY_ENV, &paths[1])])
+            .unwrap()
+            .tls;
+        assert_eq!(
+            tls,
+            Some(TlsSettings {
+                ca: None,
+                client_cert: Some((PathBuf::from(&paths[0]), PathBuf::from(&paths[1]))),
+            })
+        );
+    }
+
+    #[test]
+    fn certificate_without_key_is_incomplete() {
+        let (_dir, paths) = files(&["cert.pem"]);
+        assert!(matches!(
+            settings(&[(TLS_CERT_ENV, &paths[0])]),
+            Err(ConnectError::IncompleteClientCert)
+        ));
+    }
+
+    #[test]
+    fn missing_file_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.pem");
+        match settings(&[(TLS_CA_ENV, path.to_str().unwrap())]) {
+            Err(ConnectError::MissingFile {
+                var,
+                path: reported,
+            }) => {
+                assert_eq!(var, TLS_CA_ENV);
+                assert_eq!(reported, path);
+            }
+            other => panic!("expected a missing file, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn empty_variables_are_unset() {
+        let settings = settings(&[(TLS_CA_ENV, ""), (TOKEN_ENV, "")]).unwrap();
+        assert_eq!(settings, ConnectSettings::default());
+    }
+
+    #[test]
+    fn user_without_password_is_incomplete() {
+        assert!(matches!(
+            settings(&[(USER_ENV, "ticker")]),
+            Err(ConnectError::IncompleteUserPassword)
+        ));
+        // A token wins, so the lone user is ignored
+        let settings = settings(&[(USER_ENV, "ticker"), (TOKEN_ENV, "secret")]).unwrap();
+        assert_eq!(settings.auth, Auth::Token("secret".to_string()));
+    }
+
+    #[test]
+    fn user_and_password_are_applied() {
+        let settings = settings(&[(USER_ENV, "ticker"), (PASS_ENV, "secret")]).unwrap();
+        assert_eq!(
+            settings.auth,
+            Auth::UserPassword {
+                user: "ticker".to_string(),
+                pass: "secret".to_string(),
+            }
+        );
+        assert_eq!(format!("{:?}", settings.auth), "UserPassword(ticker, ..)");
+    }
+
+    /// Run against a TLS nats-server with e.g.
+    /// `NATS_URL=tls://localhost:4222 NATS_TLS_CA=ca.pem cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore = "needs a TLS nats-server; set NATS_URL and the NATS_TLS_* variables"]
+    async fn connects_over_tls() {
+        let client = connect(&url()).await.unwrap();
+        assert!(client.server_info().tls_required);
+        client.flush().await.unwrap();
+    }
+}
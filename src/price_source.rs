@@ -0,0 +1,9 @@
+use std::future::Future;
+
+/// Where the publisher's prices come from, asked one symbol at a time.
+pub trait PriceSource {
+    type Error: std::error::Error + Send;
+
+    /// The current price for `symbol`.
+    fn price(&mut self, symbol: &str) -> impl Future<Output = Result<f64, Self::Error>> + Send;
+}
@@ -0,0 +1,69 @@
+use async_nats::{RequestErrorKind, client::RequestError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::types::StockPrice;
+
+/// Subject answered by the quote service. The request payload is the bare symbol.
+pub const QUOTE_SUBJECT: &str = "quote.request";
+
+/// Reply to a quote request: the latest tick, or why there is none.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum QuoteReply {
+    Quote(StockPrice),
+    Error { error: String },
+}
+
+#[derive(Debug)]
+pub enum QuoteError {
+    /// Nothing is subscribed to `quote.request`.
+    NoService,
+    /// The service has not seen this symbol.
+    UnknownSymbol(String),
+    Request(RequestError),
+    /// The reply was not a quote or an error.
+    Malformed(serde_json::Error),
+}
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuoteError::NoService => {
+                write!(f, "no quote service is answering on '{}'", QUOTE_SUBJECT)
+            }
+            QuoteError::UnknownSymbol(message) => write!(f, "{}", message),
+            QuoteError::Request(e) => write!(f, "quote request failed: {}", e),
+            QuoteError::Malformed(e) => write!(f, "malformed quote reply: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for QuoteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            QuoteError::Request(e) => Some(e),
+            QuoteError::Malformed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Asks the quote service for the latest price of `symbol`.
+pub async fn request_quote(
+    client: &async_nats::Client,
+    symbol: &str,
+) -> Result<StockPrice, QuoteError> {
+    let reply = client
+        .request(QUOTE_SUBJECT, symbol.to_uppercase().into())
+        .await
+        .map_err(|e| match e.kind() {
+            RequestErrorKind::NoResponders => QuoteError::NoService,
+            _ => QuoteError::Request(e),
+        })?;
+
+    match serde_json::from_slice(&reply.payload).map_err(QuoteError::Malformed)? {
+        QuoteReply::Quote(stock) => Ok(stock),
+        QuoteReply::Error { error } => Err(QuoteError::UnknownSymbol(error)),
+    }
+}
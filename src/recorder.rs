@@ -0,0 +1,302 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// How often buffered records are flushed and fsynced to disk.
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long to wait before trying to reopen the file after a write error.
+pub const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RecordFormat {
+    Jsonl,
+    Csv,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    pub path: PathBuf,
+    pub format: RecordFormat,
+    /// Rotate once the active file would grow beyond this many bytes.
+    pub max_bytes: u64,
+    /// Number of rotated files (`<path>.1` .. `<path>.N`) to keep.
+    pub keep: usize,
+    /// Suppress failure and recovery messages, e.g. while a TUI owns the
+    /// terminal; `is_degraded` and `dropped` still report them.
+    pub quiet: bool,
+}
+
+#[derive(Serialize)]
+struct JsonRecord<'a> {
+    received_at: String,
+    subject: &'a str,
+    payload: serde_json::Value,
+}
+
+/// Append-only audit log of received payloads with size-based rotation.
+///
+/// Write failures never propagate to the caller: the recorder logs the error,
+/// marks itself degraded, and retries opening the file every `RETRY_INTERVAL`.
+pub struct Recorder {
+    config: RecorderConfig,
+    file: Option<BufWriter<File>>,
+    written: u64,
+    last_sync: Instant,
+    last_open_attempt: Instant,
+    degraded: bool,
+    dropped: u64,
+}
+
+impl Recorder {
+    pub fn open(config: RecorderConfig) -> Self {
+        let mut recorder = Recorder {
+            config,
+            file: None,
+            written: 0,
+            last_sync: Instant::now(),
+            last_open_attempt: Instant::now(),
+            degraded: false,
+            dropped: 0,
+        };
+        recorder.try_open();
+        recorder
+    }
+
+    /// True while the recorder cannot write and is dropping records.
+    pub fn is_degraded(&self) -> bool {
+        self.degraded
+    }
+
+    /// Records lost while degraded.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    pub fn record(&mut self, subject: &str, payload: &[u8]) {
+        if self.file.is_none() && !self.retry_open() {
+            self.dropped += 1;
+            return;
+        }
+
+        let line = match self.format_line(subject, payload) {
+            Ok(line) => line,
+            Err(e) => {
+                if !self.config.quiet {
+                    eprintln!("❌ Failed to format record: {}", e);
+                }
+                self.dropped += 1;
+                return;
+            }
+        };
+
+        if self.written > 0
+            && self.written + line.len() as u64 > self.config.max_bytes
+            && let Err(e) = self.rotate()
+        {
+            self.fail("rotate", e);
+            self.dropped += 1;
+            return;
+        }
+
+        let result = self.file.as_mut().map(|f| f.write_all(line.as_bytes()));
+        match result {
+            Some(Ok(())) => self.written += line.len() as u64,
+            Some(Err(e)) => {
+                self.fail("write", e);
+                self.dropped += 1;
+            }
+            None => self.dropped += 1,
+        }
+
+        if self.last_sync.elapsed() >= SYNC_INTERVAL {
+            self.sync();
+        }
+    }
+
+    /// Flushes buffered records and fsyncs them to disk. While degraded it
+    /// retries opening the file instead, so recording resumes even when no
+    /// messages arrive.
+    pub fn sync(&mut self) {
+        self.last_sync = Instant::now();
+        let result = match self.file.as_mut() {
+            Some(f) => f.flush().and_then(|_| f.get_ref().sync_data()),
+            None => {
+                self.retry_open();
+                return;
+            }
+        };
+        if let Err(e) = result {
+            self.fail("sync", e);
+        }
+    }
+
+    fn format_line(
+        &self,
+        subject: &str,
+        payload: &[u8],
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let received_at = Utc::now().to_rfc3339();
+        match self.config.format {
+            RecordFormat::Jsonl => {
+                let payload = serde_json::from_slice(payload).unwrap_or_else(|_| {
+                    serde_json::Value::String(String::from_utf8_lossy(payload).into_owned())
+                });
+                let mut line = serde_json::to_string(&JsonRecord {
+                    received_at,
+                    subject,
+                    payload,
+                })?;
+                line.push('\n');
+                Ok(line)
+            }
+            RecordFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(Vec::new());
+                writer.write_record([
+                    received_at.as_str(),
+                    subject,
+                    &String::from_utf8_lossy(payload),
+                ])?;
+                Ok(String::from_utf8(writer.into_inner()?)?)
+            }
+        }
+    }
+
+    /// Opens the file again once `RETRY_INTERVAL` has passed since the last
+    /// attempt.
+    fn retry_open(&mut self) -> bool {
+        self.last_open_attempt.elapsed() >= RETRY_INTERVAL && self.try_open()
+    }
+
+    fn try_open(&mut self) -> bool {
+        self.last_open_attempt = Instant::now();
+        match self.open_file() {
+            Ok(()) => {
+                if self.degraded && !self.config.quiet {
+                    eprintln!("✅ Recording resumed to {}", self.config.path.display());
+                }
+                self.degraded = false;
+                true
+            }
+            Err(e) => {
+                self.fail("open", e);
+                false
+            }
+        }
+    }
+
+    fn open_file(&mut self) -> io::Result<()> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.config.path)?;
+        self.written = file.metadata()?.len();
+        let mut file = BufWriter::new(file);
+        if self.written == 0 && self.config.format == RecordFormat::Csv {
+            let header = "received_at,subject,payload\n";
+            file.write_all(header.as_bytes())?;
+            self.written = header.len() as u64;
+        }
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+            file.get_ref().sync_data()?;
+        }
+
+        let rotated = |n: usize| {
+            let mut name = self.config.path.clone().into_os_string();
+            name.push(format!(".{}", n));
+            PathBuf::from(name)
+        };
+
+        if self.config.keep == 0 {
+            fs::remove_file(&self.config.path)?;
+        } else {
+            let _ = fs::remove_file(rotated(self.config.keep));
+            for n in (1..self.config.keep).rev() {
+                let from = rotated(n);
+                if from.exists() {
+                    fs::rename(&from, rotated(n + 1))?;
+                }
+            }
+            fs::rename(&self.config.path, rotated(1))?;
+        }
+
+        self.open_file()
+    }
+
+    fn fail(&mut self, action: &str, error: io::Error) {
+        if !self.degraded && !self.config.quiet {
+            eprintln!(
+                "❌ Recorder failed to {} {}: {} (retrying every {}s)",
+                action,
+                self.config.path.display(),
+                error,
+                RETRY_INTERVAL.as_secs()
+            );
+        }
+        self.degraded = true;
+        self.file = None;
+        self.last_open_attempt = Instant::now();
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.sync();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorder(path: PathBuf) -> Recorder {
+        Recorder::open(RecorderConfig {
+            path,
+            format: RecordFormat::Jsonl,
+            max_bytes: 1024 * 1024,
+            keep: 1,
+            quiet: true,
+        })
+    }
+
+    #[test]
+    fn records_are_dropped_while_degraded() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut recorder = recorder(dir.path().join("missing").join("ticks.jsonl"));
+        assert!(recorder.is_degraded());
+        recorder.record("stock_prices", b"{}");
+        recorder.record("stock_prices", b"{}");
+        assert_eq!(recorder.dropped(), 2);
+    }
+
+    #[test]
+    fn sync_reopens_a_degraded_recorder() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("ticks.jsonl");
+        let mut recorder = recorder(path.clone());
+        fs::create_dir(dir.path().join("missing")).unwrap();
+
+        recorder.sync();
+        assert!(recorder.is_degraded(), "reopened before RETRY_INTERVAL");
+
+        recorder.last_open_attempt -= RETRY_INTERVAL;
+        recorder.sync();
+        assert!(!recorder.is_degraded());
+
+        recorder.record("stock_prices", br#"{"symbol":"AAPL"}"#);
+        recorder.sync();
+        assert!(fs::read_to_string(&path).unwrap().contains("AAPL"));
+        assert_eq!(recorder.dropped(), 0);
+    }
+}
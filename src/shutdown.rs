@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Exit code used when a second signal forces the process down, matching the
+/// shell convention for SIGINT.
+pub const FORCE_EXIT_CODE: i32 = 130;
+
+/// Cancellation shared by every task of a binary.
+///
+/// Clones observe the same state: triggering any of them wakes everything
+/// waiting on `triggered`.
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Shutdown {
+            tx: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Triggers on the first SIGINT or SIGTERM (Ctrl-C elsewhere) and exits
+    /// the process with `FORCE_EXIT_CODE` on the second, for when a graceful
+    /// shutdown hangs.
+    pub fn on_signals() -> Self {
+        let shutdown = Shutdown::new();
+        let trigger = shutdown.clone();
+        tokio::spawn(async move {
+            if let Err(e) = signal().await {
+                eprintln!("⚠️  Failed to listen for shutdown signals: {}", e);
+                return;
+            }
+            trigger.trigger();
+            if signal().await.is_ok() {
+                eprintln!("🛑 Second signal received, exiting immediately");
+                std::process::exit(FORCE_EXIT_CODE);
+            }
+        });
+        shutdown
+    }
+
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    /// Completes once shutdown has been triggered, immediately if it already
+    /// has been.
+    pub async fn triggered(&self) {
+        let mut rx = self.tx.subscribe();
+        // The sender lives as long as `self`, so this cannot fail
+        let _ = rx.wait_for(|triggered| *triggered).await;
+    }
+}
+
+#[cfg(unix)]
+async fn signal() -> std::io::Result<()> {
+    use tokio::signal::unix::{SignalKind, signal};
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result,
+        _ = terminate.recv() => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+async fn signal() -> std::io::Result<()> {
+    tokio::signal::ctrl_c().await
+}
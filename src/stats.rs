@@ -0,0 +1,332 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::types::StockPrice;
+
+/// The span a rolling statistic covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsWindow {
+    /// The last N ticks.
+    Ticks(usize),
+    /// Ticks received within this long of now.
+    Duration(Duration),
+}
+
+impl FromStr for StatsWindow {
+    type Err = String;
+
+    /// Parses a tick count ("100") or a duration ("30s", "5m").
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Ok(ticks) = value.parse::<usize>() {
+            if ticks == 0 {
+                return Err("a window must hold at least one tick".to_string());
+            }
+            return Ok(StatsWindow::Ticks(ticks));
+        }
+        match humantime::parse_duration(value) {
+            Ok(duration) if !duration.is_zero() => Ok(StatsWindow::Duration(duration)),
+            Ok(_) => Err("a window must be longer than zero".to_string()),
+            Err(_) => Err(format!(
+                "'{}' is neither a tick count nor a duration such as \"30s\"",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for StatsWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsWindow::Ticks(ticks) => write!(f, "last {} ticks", ticks),
+            StatsWindow::Duration(duration) => {
+                write!(f, "last {}", humantime::format_duration(*duration))
+            }
+        }
+    }
+}
+
+/// Summary of the prices currently in a window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowStats {
+    pub count: usize,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Sample standard deviation; zero with fewer than two ticks.
+    pub stddev: f64,
+}
+
+impl fmt::Display for WindowStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} mean={:.2} min={:.2} max={:.2} stddev={:.2}",
+            self.count, self.mean, self.min, self.max, self.stddev
+        )
+    }
+}
+
+/// Mean and variance over a sliding window of prices.
+///
+/// Uses Welford's online algorithm, extended to remove samples as they leave
+/// the window, so variance stays accurate for prices far from zero where
+/// sum-of-squares cancels catastrophically.
+#[derive(Debug, Clone)]
+pub struct RollingStats {
+    window: StatsWindow,
+    samples: VecDeque<(Instant, f64)>,
+    mean: f64,
+    /// Sum of squared differences from the current mean.
+    m2: f64,
+}
+
+impl RollingStats {
+    pub fn new(window: StatsWindow) -> Self {
+        RollingStats {
+            window,
+            samples: VecDeque::new(),
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    pub fn push(&mut self, price: f64) {
+        self.push_at(price, Instant::now());
+    }
+
+    /// Like `push`, with an explicit clock for deterministic callers.
+    pub fn push_at(&mut self, price: f64, now: Instant) {
+        // Expire first, so a window that empties restarts exactly
+        self.expire(now);
+        self.samples.push_back((now, price));
+        let n = self.samples.len() as f64;
+        let delta = price - self.mean;
+        self.mean += delta / n;
+        self.m2 += delta * (price - self.mean);
+
+        if let StatsWindow::Ticks(ticks) = self.window {
+            while self.samples.len() > ticks {
+                self.pop_oldest();
+            }
+        }
+    }
+
+    /// Statistics for the window as of `now`, or `None` once it is empty.
+    pub fn stats_at(&mut self, now: Instant) -> Option<WindowStats> {
+        self.expire(now);
+        if self.samples.is_empty() {
+            return None;
+        }
+        let (min, max) = self
+            .samples
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (_, p)| {
+                (lo.min(*p), hi.max(*p))
+            });
+        let count = self.samples.len();
+        let variance = if count > 1 {
+            self.m2.max(0.0) / (count - 1) as f64
+        } else {
+            0.0
+        };
+        Some(WindowStats {
+            count,
+            mean: self.mean,
+            min,
+            max,
+            stddev: variance.sqrt(),
+        })
+    }
+
+    fn expire(&mut self, now: Instant) {
+        if let StatsWindow::Duration(duration) = self.window {
+            while self
+                .samples
+                .front()
+                .is_some_and(|(at, _)| now.duration_since(*at) > duration)
+            {
+                self.pop_oldest();
+            }
+        }
+    }
+
+    fn pop_oldest(&mut self) {
+        let Some((_, price)) = self.samples.pop_front() else {
+            return;
+        };
+        if self.samples.is_empty() {
+            // Start over exactly rather than carrying rounding error forward
+            self.mean = 0.0;
+            self.m2 = 0.0;
+            return;
+        }
+        let n = self.samples.len() as f64;
+        let delta = price - self.mean;
+        self.mean -= delta / n;
+        self.m2 -= delta * (price - self.mean);
+    }
+}
+
+/// Rolling statistics for every symbol seen.
+#[derive(Debug, Clone)]
+pub struct SymbolStats {
+    window: StatsWindow,
+    symbols: HashMap<String, RollingStats>,
+}
+
+impl SymbolStats {
+    pub fn new(window: StatsWindow) -> Self {
+        SymbolStats {
+            window,
+            symbols: HashMap::new(),
+        }
+    }
+
+    pub fn window(&self) -> StatsWindow {
+        self.window
+    }
+
+    pub fn update(&mut self, stock: &StockPrice) {
+        let window = self.window;
+        self.symbols
+            .entry(stock.symbol.clone())
+            .or_insert_with(|| RollingStats::new(window))
+            .push(stock.price);
+    }
+
+    /// Current statistics per symbol, sorted by symbol. Symbols whose window
+    /// has emptied are left out.
+    pub fn snapshot(&mut self) -> Vec<(String, WindowStats)> {
+        let now = Instant::now();
+        let mut rows: Vec<(String, WindowStats)> = self
+            .symbols
+            .iter_mut()
+            .filter_map(|(symbol, stats)| Some((symbol.clone(), stats.stats_at(now)?)))
+            .collect();
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two-pass statistics over `prices`, for comparison.
+    fn naive(prices: &[f64]) -> (f64, f64) {
+        let n = prices.len() as f64;
+        let mean = prices.iter().sum::<f64>() / n;
+        let variance = if prices.len() > 1 {
+            prices.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / (n - 1.0)
+        } else {
+            0.0
+        };
+        (mean, variance.sqrt())
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        let tolerance = 1e-6 * expected.abs().max(1.0);
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn parses_windows() {
+        assert_eq!("100".parse(), Ok(StatsWindow::Ticks(100)));
+        assert_eq!(
+            "30s".parse(),
+            Ok(StatsWindow::Duration(Duration::from_secs(30)))
+        );
+        assert!("0".parse::<StatsWindow>().is_err());
+        assert!("0s".parse::<StatsWindow>().is_err());
+        assert!("soon".parse::<StatsWindow>().is_err());
+    }
+
+    #[test]
+    fn tick_window_matches_naive_recompute() {
+        let mut stats = RollingStats::new(StatsWindow::Ticks(5));
+        let now = Instant::now();
+        // Prices far from zero, where sum-of-squares variance breaks down
+        let prices: Vec<f64> = (0..200)
+            .map(|i| 1_000_000.0 + ((i * 37) % 11) as f64 * 0.25)
+            .collect();
+        for (i, price) in prices.iter().enumerate() {
+            stats.push_at(*price, now);
+            let window = &prices[i.saturating_sub(4)..=i];
+            let (mean, stddev) = naive(window);
+            let got = stats.stats_at(now).unwrap();
+            assert_eq!(got.count, window.len());
+            assert_close(got.mean, mean);
+            assert_close(got.stddev, stddev);
+            assert_eq!(
+                got.min,
+                window.iter().copied().fold(f64::INFINITY, f64::min)
+            );
+            assert_eq!(
+                got.max,
+                window.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+            );
+        }
+    }
+
+    #[test]
+    fn duration_window_expires_old_samples() {
+        let mut stats = RollingStats::new(StatsWindow::Duration(Duration::from_secs(30)));
+        let start = Instant::now();
+        stats.push_at(100.0, start);
+        stats.push_at(200.0, start + Duration::from_secs(10));
+        stats.push_at(300.0, start + Duration::from_secs(20));
+
+        let all = stats.stats_at(start + Duration::from_secs(30)).unwrap();
+        assert_eq!(all.count, 3);
+        assert_close(all.mean, 200.0);
+
+        let later = stats.stats_at(start + Duration::from_secs(35)).unwrap();
+        assert_eq!(later.count, 2);
+        assert_close(later.mean, 250.0);
+        assert_close(later.stddev, naive(&[200.0, 300.0]).1);
+        assert_eq!(later.min, 200.0);
+
+        assert!(stats.stats_at(start + Duration::from_secs(51)).is_none());
+    }
+
+    #[test]
+    fn restarts_exactly_after_emptying() {
+        let mut stats = RollingStats::new(StatsWindow::Duration(Duration::from_secs(1)));
+        let start = Instant::now();
+        stats.push_at(1e9, start);
+        stats.push_at(1e9 + 1.0, start);
+        let later = start + Duration::from_secs(5);
+        stats.push_at(5.0, later);
+        let got = stats.stats_at(later).unwrap();
+        assert_eq!(got.count, 1);
+        assert_eq!(got.mean, 5.0);
+        assert_eq!(got.stddev, 0.0);
+    }
+
+    #[test]
+    fn symbols_are_tracked_separately() {
+        let mut stats = SymbolStats::new(StatsWindow::Ticks(10));
+        for (symbol, price) in [("MSFT", 10.0), ("AAPL", 1.0), ("MSFT", 20.0)] {
+            stats.update(&StockPrice {
+                symbol: symbol.to_string(),
+                price,
+                ..StockPrice::default()
+            });
+        }
+        let rows = stats.snapshot();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].0, "AAPL");
+        assert_eq!(rows[0].1.count, 1);
+        assert_eq!(rows[1].0, "MSFT");
+        assert_close(rows[1].1.mean, 15.0);
+    }
+}
@@ -0,0 +1,147 @@
+use chrono::Utc;
+use sqlx::{
+    SqlitePool,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+};
+use std::{path::PathBuf, time::Duration};
+
+use crate::types::StockPrice;
+
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(1000);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS ticks (
+        symbol TEXT NOT NULL,
+        price REAL NOT NULL,
+        ts TEXT NOT NULL,
+        received_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS ticks_symbol_ts ON ticks (symbol, ts);
+";
+
+#[derive(Debug, Clone)]
+pub struct StoreConfig {
+    pub path: PathBuf,
+    /// Flush once this many rows are pending.
+    pub batch_size: usize,
+    /// Use write-ahead logging so other processes can read while we write.
+    pub wal: bool,
+}
+
+struct Row {
+    symbol: String,
+    price: f64,
+    ts: String,
+    received_at: String,
+}
+
+/// Batched writer of ticks into a SQLite `ticks` table.
+///
+/// Rows are buffered and written in one transaction per batch. A batch that
+/// fails is retried once and then dropped and counted in `failed`.
+pub struct TickStore {
+    pool: SqlitePool,
+    batch_size: usize,
+    pending: Vec<Row>,
+    written: u64,
+    failed: u64,
+}
+
+impl TickStore {
+    /// Opens (creating if needed) the database and its schema.
+    pub async fn open(config: StoreConfig) -> Result<Self, sqlx::Error> {
+        let journal_mode = if config.wal {
+            SqliteJournalMode::Wal
+        } else {
+            SqliteJournalMode::Delete
+        };
+        let options = SqliteConnectOptions::new()
+            .filename(&config.path)
+            .create_if_missing(true)
+            .journal_mode(journal_mode);
+        // A single writer connection keeps batches strictly ordered
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+        sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+
+        Ok(TickStore {
+            pool,
+            batch_size: config.batch_size.max(1),
+            pending: Vec::with_capacity(config.batch_size.max(1)),
+            written: 0,
+            failed: 0,
+        })
+    }
+
+    /// Queues a tick, flushing when the batch is full.
+    pub async fn insert(&mut self, stock: &StockPrice) {
+        self.pending.push(Row {
+            symbol: stock.symbol.clone(),
+            price: stock.price,
+            ts: stock.timestamp.clone(),
+            received_at: Utc::now().to_rfc3339(),
+        });
+        if self.pending.len() >= self.batch_size {
+            self.flush().await;
+        }
+    }
+
+    /// Writes all pending rows.
+    pub async fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let result = match self.write_batch().await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                eprintln!("⚠️  SQLite batch insert failed, retrying once: {}", e);
+                self.write_batch().await
+            }
+        };
+        let rows = self.pending.len() as u64;
+        match result {
+            Ok(()) => self.written += rows,
+            Err(e) => {
+                eprintln!(
+                    "❌ Dropping {} ticks after SQLite insert failed: {}",
+                    rows, e
+                );
+                self.failed += rows;
+            }
+        }
+        self.pending.clear();
+    }
+
+    /// Rows committed so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Rows dropped after a failed retry.
+    pub fn failed(&self) -> u64 {
+        self.failed
+    }
+
+    async fn write_batch(&self) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for row in &self.pending {
+            sqlx::query("INSERT INTO ticks (symbol, price, ts, received_at) VALUES (?, ?, ?, ?)")
+                .bind(&row.symbol)
+                .bind(row.price)
+                .bind(&row.ts)
+                .bind(&row.received_at)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Flushes pending rows and closes the database.
+    pub async fn close(mut self) {
+        self.flush().await;
+        self.pool.close().await;
+    }
+}
@@ -0,0 +1,692 @@
+use async_nats::jetstream::message::Acker;
+use bytes::Bytes;
+use futures::{Stream, StreamExt, stream};
+use serde::de::DeserializeOwned;
+use std::{borrow::Cow, fmt, pin::Pin, time::Duration};
+
+use crate::batch::{
+    BATCH_COUNT_HEADER, BatchError, CONTENT_ENCODING_HEADER, batch_subject, decode_batch, gunzip,
+    is_batch_subject,
+};
+use crate::dedup::DedupWindow;
+use crate::dispatch::{Dispatcher, MessageKind};
+use crate::filter::TickFilter;
+use crate::jetstream::{JetStreamError, ReplayFrom, durable_messages};
+use crate::nats::{self, ConnectError};
+use crate::types::{MarketStatus, PayloadError, StockBar, StockPrice};
+use crate::wire::WireFormat;
+
+#[derive(Debug)]
+pub enum SubscribeError {
+    Connect(ConnectError),
+    Subscribe(async_nats::SubscribeError),
+    JetStream(JetStreamError),
+    /// The message stream failed; later messages may still arrive.
+    Receive(async_nats::Error),
+    /// No payload type is routed for the subject.
+    UnknownSubject(String),
+    /// A single tick that could not be decoded or failed validation.
+    Payload(PayloadError),
+    /// A batch or compressed payload that could not be decoded.
+    Batch(BatchError),
+}
+
+impl fmt::Display for SubscribeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubscribeError::Connect(e) => write!(f, "{}", e),
+            SubscribeError::Subscribe(e) => write!(f, "failed to subscribe: {}", e),
+            SubscribeError::JetStream(e) => write!(f, "{}", e),
+            SubscribeError::Receive(e) => write!(f, "failed to receive message: {}", e),
+            SubscribeError::UnknownSubject(subject) => write!(f, "unknown subject '{}'", subject),
+            SubscribeError::Payload(e) => write!(f, "{}", e),
+            SubscribeError::Batch(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SubscribeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SubscribeError::Connect(e) => Some(e),
+            SubscribeError::Subscribe(e) => Some(e),
+            SubscribeError::JetStream(e) => Some(e),
+            SubscribeError::Receive(e) => Some(e.as_ref()),
+            SubscribeError::UnknownSubject(_) => None,
+            SubscribeError::Payload(e) => Some(e),
+            SubscribeError::Batch(e) => Some(e),
+        }
+    }
+}
+
+/// What `StockSubscriber` subscribes to and how it decodes.
+///
+/// Defaults match the binaries: the `NATS_URL` server (or the local one),
+/// the `NATS_SUBJECT` tick subject, the `WIRE_FORMAT` format, no filter and
+/// a 10,000-entry, two-minute deduplication window.
+#[derive(Debug, Clone)]
+pub struct SubscriberOptions {
+    url: String,
+    tick_subject: String,
+    subjects: Vec<String>,
+    queue_group: Option<String>,
+    durable: Option<DurableConfig>,
+    format: WireFormat,
+    filter: TickFilter,
+    dedup_size: usize,
+    dedup_ttl: Duration,
+}
+
+/// A durable JetStream consumer on the tick subject.
+#[derive(Debug, Clone)]
+pub struct DurableConfig {
+    pub name: String,
+    /// Stream to bind to; by default the stream capturing the tick subject.
+    pub stream: Option<String>,
+    /// Where a newly created consumer starts.
+    pub replay_from: Option<ReplayFrom>,
+}
+
+impl Default for SubscriberOptions {
+    fn default() -> Self {
+        SubscriberOptions {
+            url: nats::url(),
+            tick_subject: nats::subject(),
+            subjects: Vec::new(),
+            queue_group: None,
+            durable: None,
+            format: WireFormat::from_env().unwrap_or_default(),
+            filter: TickFilter::new(),
+            dedup_size: 10_000,
+            dedup_ttl: Duration::from_secs(120),
+        }
+    }
+}
+
+impl SubscriberOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    /// The subject ticks are published on. Subjects are routed to payload
+    /// types as in `Dispatcher::standard` for this subject.
+    pub fn tick_subject(mut self, subject: impl Into<String>) -> Self {
+        self.tick_subject = subject.into();
+        self
+    }
+
+    /// Adds a subject or wildcard pattern; without any, the tick subject
+    /// and its batch subject are used.
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subjects.push(subject.into());
+        self
+    }
+
+    /// Joins a queue group, so subscribers in it share the messages.
+    pub fn queue_group(mut self, group: impl Into<String>) -> Self {
+        self.queue_group = Some(group.into());
+        self
+    }
+
+    /// Consumes through a durable JetStream consumer instead of plain
+    /// subscriptions; the subjects and queue group are then ignored.
+    pub fn durable(mut self, durable: DurableConfig) -> Self {
+        self.durable = Some(durable);
+        self
+    }
+
+    pub fn wire_format(mut self, format: WireFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Ticks the filter rejects are skipped without an error.
+    pub fn filter(mut self, filter: TickFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Remembers up to `size` `Nats-Msg-Id` values for `ttl`; a size of 0
+    /// disables deduplication.
+    pub fn dedup(mut self, size: usize, ttl: Duration) -> Self {
+        self.dedup_size = size;
+        self.dedup_ttl = ttl;
+        self
+    }
+
+    /// The subjects plain subscriptions are made on, in order.
+    pub fn subscriptions(&self) -> Vec<String> {
+        if self.subjects.is_empty() {
+            vec![batch_subject(&self.tick_subject), self.tick_subject.clone()]
+        } else {
+            self.subjects.clone()
+        }
+    }
+}
+
+/// One received message and what it decoded to.
+pub struct Delivery {
+    /// The message as received, before inflating, e.g. for recording.
+    pub message: async_nats::Message,
+    /// Set for JetStream deliveries, which the caller must acknowledge.
+    pub acker: Option<Acker>,
+    /// Everything the message held, in order: one entry for a single tick,
+    /// bar or status, one per tick for a batch.
+    pub items: Vec<Decoded>,
+}
+
+#[derive(Debug)]
+pub enum Decoded {
+    /// A tick the filter accepted.
+    Tick(StockPrice),
+    /// A valid tick the filter rejected.
+    Filtered(StockPrice),
+    Bar(StockBar),
+    Status(MarketStatus),
+    /// The `Nats-Msg-Id` was already seen within the deduplication window.
+    Duplicate,
+    Error(SubscribeError),
+}
+
+/// Stream of deliveries from `StockSubscriber::subscribe` or `from_frames`.
+pub type Deliveries = Pin<Box<dyn Stream<Item = Result<Delivery, SubscribeError>> + Send>>;
+
+type Messages = Pin<
+    Box<dyn Stream<Item = Result<(async_nats::Message, Option<Acker>), SubscribeError>> + Send>,
+>;
+
+/// Typed price stream over NATS for programs embedding this crate.
+pub struct StockSubscriber;
+
+impl StockSubscriber {
+    /// Connects and subscribes, returning every valid tick as it arrives.
+    ///
+    /// Batches and gzip payloads are unpacked, duplicates by `Nats-Msg-Id`
+    /// dropped and filtered ticks, bars and market status skipped.
+    /// Undecodable payloads, invalid ticks and subjects with no known
+    /// payload type are yielded as errors so the caller can decide whether
+    /// to carry on. JetStream messages are acknowledged as they are
+    /// yielded. Reconnection and resubscription after a dropped connection
+    /// are handled by the NATS client.
+    pub async fn connect(
+        options: SubscriberOptions,
+    ) -> Result<impl Stream<Item = Result<StockPrice, SubscribeError>> + Send + use<>, SubscribeError>
+    {
+        let client = nats::connect(&options.url)
+            .await
+            .map_err(SubscribeError::Connect)?;
+        let deliveries = StockSubscriber::subscribe(&client, options).await?;
+        let ticks = deliveries
+            .then(|delivery| async move {
+                let delivery = match delivery {
+                    Ok(delivery) => delivery,
+                    Err(e) => return vec![Err(e)],
+                };
+                if let Some(acker) = delivery.acker.as_ref() {
+                    // Redelivery is the caller's concern only with subscribe
+                    let _ = acker.ack().await;
+                }
+                delivery
+                    .items
+                    .into_iter()
+                    .filter_map(|item| match item {
+                        Decoded::Tick(tick) => Some(Ok(tick)),
+                        Decoded::Error(e) => Some(Err(e)),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .flat_map(stream::iter);
+        Ok(ticks)
+    }
+
+    /// Subscribes on an existing connection and yields every message with
+    /// what it decoded to, for callers that need the raw message, bars and
+    /// market status, or control over JetStream acknowledgement.
+    pub async fn subscribe(
+        client: &async_nats::Client,
+        options: SubscriberOptions,
+    ) -> Result<Deliveries, SubscribeError> {
+        let messages: Messages = match options.durable.as_ref() {
+            Some(durable) => {
+                let messages = durable_messages(
+                    client.clone(),
+                    &options.tick_subject,
+                    durable.stream.as_deref(),
+                    &durable.name,
+                    durable.replay_from,
+                )
+                .await
+                .map_err(SubscribeError::JetStream)?;
+                Box::pin(messages.map(|message| {
+                    message
+                        .map(|message| {
+                            let (message, acker) = message.split();
+                            (message, Some(acker))
+                        })
+                        .map_err(|e| SubscribeError::Receive(e.into()))
+                }))
+            }
+            None => {
+                let mut subscribers = Vec::new();
+                for subject in options.subscriptions() {
+                    let subscriber = match options.queue_group.as_ref() {
+                        Some(group) => client.queue_subscribe(subject, group.clone()).await,
+                        None => client.subscribe(subject).await,
+                    };
+                    subscribers.push(subscriber.map_err(SubscribeError::Subscribe)?);
+                }
+                Box::pin(stream::select_all(subscribers).map(|message| Ok((message, None))))
+            }
+        };
+        Ok(deliveries(messages, options))
+    }
+
+    /// Decodes a stream of bare tick payloads, such as the aggregator's
+    /// WebSocket frames, as if each arrived on the tick subject without
+    /// headers.
+    pub fn from_frames(
+        frames: impl Stream<Item = Bytes> + Send + 'static,
+        options: SubscriberOptions,
+    ) -> Deliveries {
+        let subject = async_nats::Subject::from(options.tick_subject.as_str());
+        let messages = frames.map(move |payload| {
+            let message = async_nats::Message {
+                subject: subject.clone(),
+                reply: None,
+                length: payload.len(),
+                payload,
+                headers: None,
+                status: None,
+                description: None,
+            };
+            Ok((message, None))
+        });
+        deliveries(Box::pin(messages), options)
+    }
+}
+
+fn deliveries(messages: Messages, options: SubscriberOptions) -> Deliveries {
+    let mut decoder = Decoder::new(options);
+    Box::pin(messages.map(move |message| {
+        message.map(|(message, acker)| Delivery {
+            items: decoder.items(&message),
+            message,
+            acker,
+        })
+    }))
+}
+
+/// Deduplicates, routes, decodes and filters messages.
+struct Decoder {
+    format: WireFormat,
+    dispatcher: Dispatcher,
+    filter: TickFilter,
+    dedup: DedupWindow,
+}
+
+impl Decoder {
+    fn new(options: SubscriberOptions) -> Self {
+        Decoder {
+            format: options.format,
+            dispatcher: Dispatcher::standard(&options.tick_subject),
+            filter: options.filter,
+            dedup: DedupWindow::new(options.dedup_size, options.dedup_ttl),
+        }
+    }
+
+    fn items(&mut self, message: &async_nats::Message) -> Vec<Decoded> {
+        if message_id(message).is_some_and(|id| !self.dedup.insert(&id)) {
+            return vec![Decoded::Duplicate];
+        }
+        match self.dispatcher.kind_of(&message.subject) {
+            Some(MessageKind::Price) => decode_nats_message(self.format, message)
+                .into_iter()
+                .map(|tick| match tick {
+                    Ok(tick) if self.filter.accepts(&tick) => Decoded::Tick(tick),
+                    Ok(tick) => Decoded::Filtered(tick),
+                    Err(e) => Decoded::Error(e),
+                })
+                .collect(),
+            Some(MessageKind::Bar) => vec![self.decode_as(message, Decoded::Bar)],
+            Some(MessageKind::Status) => vec![self.decode_as(message, Decoded::Status)],
+            None => vec![Decoded::Error(SubscribeError::UnknownSubject(
+                message.subject.to_string(),
+            ))],
+        }
+    }
+
+    fn decode_as<T: DeserializeOwned>(
+        &self,
+        message: &async_nats::Message,
+        wrap: fn(T) -> Decoded,
+    ) -> Decoded {
+        let decoded = inflated_payload(message)
+            .map_err(SubscribeError::Batch)
+            .and_then(|payload| {
+                self.format
+                    .decode_as(&payload)
+                    .map_err(SubscribeError::Payload)
+            });
+        match decoded {
+            Ok(value) => wrap(value),
+            Err(e) => Decoded::Error(e),
+        }
+    }
+}
+
+/// Decodes a NATS message into its ticks, honouring its `Content-Encoding`
+/// and `Batch-Count` headers.
+pub fn decode_nats_message(
+    format: WireFormat,
+    message: &async_nats::Message,
+) -> Vec<Result<StockPrice, SubscribeError>> {
+    let payload = match inflated_payload(message) {
+        Ok(payload) => payload,
+        Err(e) => return vec![Err(SubscribeError::Batch(e))],
+    };
+    let batch_count = header(message, BATCH_COUNT_HEADER).and_then(|count| count.parse().ok());
+    decode_message(format, &message.subject, &payload, batch_count)
+}
+
+/// The `Nats-Msg-Id` header deduplication keys on; messages without one
+/// are never duplicates.
+pub fn message_id(message: &async_nats::Message) -> Option<String> {
+    header(message, async_nats::header::NATS_MESSAGE_ID.as_ref())
+}
+
+/// The payload, inflated when `Content-Encoding: gzip` is set.
+pub fn inflated_payload(message: &async_nats::Message) -> Result<Cow<'_, [u8]>, BatchError> {
+    let gzip =
+        header(message, CONTENT_ENCODING_HEADER).is_some_and(|e| e.eq_ignore_ascii_case("gzip"));
+    if gzip {
+        gunzip(&message.payload).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(&message.payload))
+    }
+}
+
+fn header(message: &async_nats::Message, name: &str) -> Option<String> {
+    message
+        .headers
+        .as_ref()
+        .and_then(|headers| headers.get(name))
+        .map(|value| value.as_str().to_string())
+}
+
+/// Decodes an inflated payload into its ticks: each tick of a batch when
+/// it came in on a batch subject or declares a `Batch-Count`, otherwise a
+/// single tick.
+pub fn decode_message(
+    format: WireFormat,
+    subject: &str,
+    payload: &[u8],
+    batch_count: Option<usize>,
+) -> Vec<Result<StockPrice, SubscribeError>> {
+    if batch_count.is_none() && !is_batch_subject(subject) {
+        return vec![format.decode(payload).map_err(SubscribeError::Payload)];
+    }
+    match decode_batch(format, payload, batch_count) {
+        Ok(ticks) => ticks
+            .into_iter()
+            .map(|tick| tick.validated().map_err(SubscribeError::Payload))
+            .collect(),
+        Err(e) => vec![Err(SubscribeError::Batch(e))],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch::TickBatch;
+
+    fn tick(symbol: &str) -> StockPrice {
+        StockPrice {
+            symbol: symbol.to_string(),
+            price: 1.0,
+            ..StockPrice::default()
+        }
+    }
+
+    fn symbols(results: Vec<Result<StockPrice, SubscribeError>>) -> Vec<String> {
+        results
+            .into_iter()
+            .map(|tick| tick.unwrap().symbol)
+            .collect()
+    }
+
+    #[test]
+    fn single_tick_on_tick_subject() {
+        let payload = WireFormat::Json.encode(&tick("AAPL")).unwrap();
+        let ticks = decode_message(WireFormat::Json, "stock_prices", &payload, None);
+        assert_eq!(symbols(ticks), ["AAPL"]);
+    }
+
+    #[test]
+    fn batch_on_batch_subject_or_with_count() {
+        let batch = TickBatch {
+            ticks: vec![tick("AAPL"), tick("MSFT")],
+        };
+        let payload = WireFormat::Json.encode(&batch).unwrap();
+        let by_subject = decode_message(WireFormat::Json, "stock_prices.batch", &payload, None);
+        assert_eq!(symbols(by_subject), ["AAPL", "MSFT"]);
+        let by_header = decode_message(WireFormat::Json, "custom", &payload, Some(2));
+        assert_eq!(symbols(by_header), ["AAPL", "MSFT"]);
+    }
+
+    #[test]
+    fn batch_envelope_on_tick_subject_is_malformed() {
+        let batch = TickBatch {
+            ticks: vec![tick("AAPL")],
+        };
+        let payload = WireFormat::Json.encode(&batch).unwrap();
+        let ticks = decode_message(WireFormat::Json, "stock_prices", &payload, None);
+        assert!(matches!(
+            ticks.as_slice(),
+            [Err(SubscribeError::Payload(PayloadError::Malformed(_)))]
+        ));
+    }
+
+    #[test]
+    fn invalid_ticks_in_a_batch_fail_alone() {
+        let batch = TickBatch {
+            ticks: vec![
+                tick("AAPL"),
+                StockPrice {
+                    price: -1.0,
+                    ..tick("BAD")
+                },
+            ],
+        };
+        let payload = WireFormat::Json.encode(&batch).unwrap();
+        let ticks = decode_message(WireFormat::Json, "stock_prices.batch", &payload, None);
+        assert!(matches!(
+            ticks.as_slice(),
+            [
+                Ok(_),
+                Err(SubscribeError::Payload(PayloadError::Invalid { .. }))
+            ]
+        ));
+    }
+
+    fn nats_message(
+        subject: &str,
+        payload: Vec<u8>,
+        headers: &[(&str, &str)],
+    ) -> async_nats::Message {
+        let mut map = async_nats::HeaderMap::new();
+        for (name, value) in headers {
+            map.insert(*name, *value);
+        }
+        async_nats::Message {
+            subject: subject.into(),
+            reply: None,
+            length: payload.len(),
+            payload: payload.into(),
+            headers: (!headers.is_empty()).then_some(map),
+            status: None,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn gzip_messages_are_inflated() {
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&WireFormat::Json.encode(&tick("AAPL")).unwrap())
+            .unwrap();
+        let message = nats_message(
+            "stock_prices",
+            encoder.finish().unwrap(),
+            &[(CONTENT_ENCODING_HEADER, "gzip")],
+        );
+        assert_eq!(
+            symbols(decode_nats_message(WireFormat::Json, &message)),
+            ["AAPL"]
+        );
+
+        let garbage = nats_message(
+            "stock_prices",
+            b"not gzip".to_vec(),
+            &[(CONTENT_ENCODING_HEADER, "gzip")],
+        );
+        assert!(matches!(
+            decode_nats_message(WireFormat::Json, &garbage).as_slice(),
+            [Err(SubscribeError::Batch(BatchError::Decompress(_)))]
+        ));
+    }
+
+    #[test]
+    fn batch_count_header_is_honoured() {
+        let batch = TickBatch {
+            ticks: vec![tick("AAPL")],
+        };
+        let payload = WireFormat::Json.encode(&batch).unwrap();
+        let message = nats_message("custom", payload, &[(BATCH_COUNT_HEADER, "2")]);
+        assert!(matches!(
+            decode_nats_message(WireFormat::Json, &message).as_slice(),
+            [Err(SubscribeError::Batch(BatchError::CountMismatch {
+                declared: 2,
+                found: 1
+            }))]
+        ));
+    }
+
+    #[test]
+    fn message_id_reads_the_nats_header() {
+        let with_id = nats_message("s", Vec::new(), &[("Nats-Msg-Id", "abc")]);
+        assert_eq!(message_id(&with_id).as_deref(), Some("abc"));
+        assert_eq!(message_id(&nats_message("s", Vec::new(), &[])), None);
+    }
+
+    fn options() -> SubscriberOptions {
+        SubscriberOptions::new()
+            .tick_subject("stock_prices")
+            .wire_format(WireFormat::Json)
+    }
+
+    fn json(value: &impl serde::Serialize) -> Vec<u8> {
+        WireFormat::Json.encode(value).unwrap()
+    }
+
+    #[test]
+    fn default_subscriptions_cover_ticks_and_batches() {
+        assert_eq!(
+            options().subscriptions(),
+            ["stock_prices.batch", "stock_prices"]
+        );
+        assert_eq!(
+            options().subject("stock_bars.>").subscriptions(),
+            ["stock_bars.>"]
+        );
+    }
+
+    #[test]
+    fn duplicates_are_reported_once_seen() {
+        let mut decoder = Decoder::new(options());
+        let message = nats_message("stock_prices", json(&tick("AAPL")), &[("Nats-Msg-Id", "1")]);
+        assert!(matches!(
+            decoder.items(&message).as_slice(),
+            [Decoded::Tick(_)]
+        ));
+        assert!(matches!(
+            decoder.items(&message).as_slice(),
+            [Decoded::Duplicate]
+        ));
+    }
+
+    #[test]
+    fn filtered_ticks_are_kept_apart() {
+        let mut decoder = Decoder::new(options().filter(TickFilter::new().symbols(["AAPL"])));
+        let batch = TickBatch {
+            ticks: vec![tick("AAPL"), tick("MSFT")],
+        };
+        let message = nats_message("stock_prices.batch", json(&batch), &[]);
+        assert!(matches!(
+            decoder.items(&message).as_slice(),
+            [Decoded::Tick(aapl), Decoded::Filtered(msft)]
+                if aapl.symbol == "AAPL" && msft.symbol == "MSFT"
+        ));
+    }
+
+    #[test]
+    fn subjects_are_routed_to_payload_types() {
+        let mut decoder = Decoder::new(options());
+        let bar = StockBar {
+            symbol: "AAPL".to_string(),
+            open: 1.0,
+            high: 2.0,
+            low: 0.5,
+            close: 1.5,
+            volume: 10,
+            timestamp: String::new(),
+        };
+        let message = nats_message("stock_bars.AAPL", json(&bar), &[]);
+        assert!(matches!(
+            decoder.items(&message).as_slice(),
+            [Decoded::Bar(_)]
+        ));
+
+        let status = MarketStatus {
+            status: "open".to_string(),
+            timestamp: String::new(),
+        };
+        let message = nats_message("market_status", json(&status), &[]);
+        assert!(matches!(
+            decoder.items(&message).as_slice(),
+            [Decoded::Status(_)]
+        ));
+
+        let message = nats_message("other", json(&tick("AAPL")), &[]);
+        assert!(matches!(
+            decoder.items(&message).as_slice(),
+            [Decoded::Error(SubscribeError::UnknownSubject(subject))] if subject == "other"
+        ));
+    }
+
+    #[tokio::test]
+    async fn frames_decode_as_the_tick_subject() {
+        let frames = stream::iter([Bytes::from(json(&tick("AAPL"))), Bytes::from("garbage")]);
+        let deliveries: Vec<_> = StockSubscriber::from_frames(frames, options())
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].message.subject.as_str(), "stock_prices");
+        assert!(matches!(deliveries[0].items.as_slice(), [Decoded::Tick(_)]));
+        assert!(matches!(
+            deliveries[1].items.as_slice(),
+            [Decoded::Error(SubscribeError::Payload(
+                PayloadError::Malformed(_)
+            ))]
+        ));
+    }
+}
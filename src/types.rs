@@ -1,8 +1,271 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, de};
+use utoipa::ToSchema;
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Unknown fields are ignored so publishers can add fields without breaking
+/// older consumers.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, ToSchema)]
 pub struct StockPrice {
     pub symbol: String,
+    /// Accepts a JSON number or a numeric string such as `"123.45"`.
+    #[serde(deserialize_with = "number_or_string")]
     pub price: f64,
+    /// Empty when the publisher did not send one.
+    #[serde(default)]
     pub timestamp: String,
+    /// Per-publisher message number, starting at 1; 0 from publishers that
+    /// do not number their messages.
+    #[serde(default)]
+    pub seq: u64,
+    /// Which publisher sent the tick; empty when not sent.
+    #[serde(default)]
+    pub publisher_id: String,
+}
+
+/// An OHLC bar, published on `stock_bars.<symbol>`.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct StockBar {
+    pub symbol: String,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    #[serde(default)]
+    pub volume: u64,
+    /// Start of the bar's interval.
+    #[serde(default)]
+    pub timestamp: String,
+}
+
+/// Trading session announcement, published on `market_status`.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct MarketStatus {
+    /// e.g. "open", "closed" or "halted".
+    pub status: String,
+    #[serde(default)]
+    pub timestamp: String,
+}
+
+fn number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    struct PriceVisitor;
+
+    impl de::Visitor<'_> for PriceVisitor {
+        type Value = f64;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "a number or a numeric string")
+        }
+
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<f64, E> {
+            Ok(value)
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<f64, E> {
+            Ok(value as f64)
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<f64, E> {
+            Ok(value as f64)
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<f64, E> {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+        }
+    }
+
+    deserializer.deserialize_any(PriceVisitor)
+}
+
+/// Why a `StockPrice` was rejected at the ingestion boundary.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// `NaN` or an infinity, which would poison any running statistics.
+    NonFinitePrice(f64),
+    /// Stock prices cannot be negative.
+    NegativePrice(f64),
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::NonFinitePrice(price) => write!(f, "price {} is not finite", price),
+            ValidationError::NegativePrice(price) => write!(f, "price {} is negative", price),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Why a NATS payload could not be turned into a `StockPrice`.
+#[derive(Debug)]
+pub enum PayloadError {
+    /// Not a `StockPrice` in the expected wire format.
+    Malformed(Box<dyn std::error::Error + Send + Sync>),
+    /// Parsed, but failed `StockPrice::validate`.
+    Invalid {
+        symbol: String,
+        error: ValidationError,
+    },
+}
+
+impl std::fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PayloadError::Malformed(e) => write!(f, "malformed payload: {}", e),
+            PayloadError::Invalid { symbol, error } => {
+                write!(f, "invalid {} tick: {}", symbol, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PayloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PayloadError::Malformed(e) => Some(e.as_ref()),
+            PayloadError::Invalid { error, .. } => Some(error),
+        }
+    }
+}
+
+impl StockPrice {
+    /// Checks the invariants every ingested price must hold: the price is a
+    /// finite number and not negative. Zero is accepted.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if !self.price.is_finite() {
+            return Err(ValidationError::NonFinitePrice(self.price));
+        }
+        if self.price < 0.0 {
+            return Err(ValidationError::NegativePrice(self.price));
+        }
+        Ok(())
+    }
+
+    /// Parses and validates a raw JSON message payload.
+    pub fn from_bytes(payload: &[u8]) -> Result<Self, PayloadError> {
+        Self::try_from(payload)
+    }
+
+    /// Passes the tick through if `validate` accepts it.
+    pub fn validated(self) -> Result<Self, PayloadError> {
+        match self.validate() {
+            Ok(()) => Ok(self),
+            Err(error) => Err(PayloadError::Invalid {
+                symbol: self.symbol,
+                error,
+            }),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for StockPrice {
+    type Error = PayloadError;
+
+    fn try_from(payload: &[u8]) -> Result<Self, Self::Error> {
+        serde_json::from_slice::<StockPrice>(payload)
+            .map_err(|e| PayloadError::Malformed(e.into()))?
+            .validated()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_fields_are_ignored() {
+        let tick: StockPrice = serde_json::from_str(
+            r#"{"symbol":"AAPL","price":1.5,"exchange":"NASDAQ","extra":{"a":1}}"#,
+        )
+        .unwrap();
+        assert_eq!(tick.symbol, "AAPL");
+        assert_eq!(tick.price, 1.5);
+    }
+
+    #[test]
+    fn missing_optional_fields_default() {
+        let tick: StockPrice = serde_json::from_str(r#"{"symbol":"AAPL","price":1.5}"#).unwrap();
+        assert_eq!(tick.timestamp, "");
+        assert_eq!(tick.seq, 0);
+        assert_eq!(tick.publisher_id, "");
+    }
+
+    #[test]
+    fn string_price_parses() {
+        let tick: StockPrice =
+            serde_json::from_str(r#"{"symbol":"AAPL","price":"123.45"}"#).unwrap();
+        assert_eq!(tick.price, 123.45);
+    }
+
+    #[test]
+    fn valid_bytes_convert() {
+        let payload =
+            br#"{"symbol":"TSLA","price":250.25,"timestamp":"t","seq":3,"publisher_id":"p"}"#;
+        let tick = StockPrice::try_from(&payload[..]).unwrap();
+        assert_eq!(tick.symbol, "TSLA");
+        assert_eq!(tick.price, 250.25);
+        assert_eq!(tick.seq, 3);
+        assert_eq!(tick.publisher_id, "p");
+    }
+
+    #[test]
+    fn malformed_bytes_are_rejected() {
+        let missing_price = br#"{"symbol":"TSLA"}"#;
+        assert!(matches!(
+            StockPrice::try_from(&missing_price[..]),
+            Err(PayloadError::Malformed(_))
+        ));
+        let invalid = br#"{"symbol":"TSLA","price":-3}"#;
+        assert!(matches!(
+            StockPrice::try_from(&invalid[..]),
+            Err(PayloadError::Invalid { symbol, error: ValidationError::NegativePrice(_) }) if symbol == "TSLA"
+        ));
+    }
+
+    #[test]
+    fn garbage_is_malformed() {
+        for payload in [&b"not json"[..], br#"{"symbol":"AAPL","price":"abc"}"#, b""] {
+            assert!(matches!(
+                StockPrice::from_bytes(payload),
+                Err(PayloadError::Malformed(_))
+            ));
+        }
+    }
+
+    fn priced(price: f64) -> StockPrice {
+        StockPrice {
+            symbol: "AAPL".to_string(),
+            price,
+            ..StockPrice::default()
+        }
+    }
+
+    #[test]
+    fn negative_price_is_rejected() {
+        assert_eq!(
+            priced(-1.0).validate(),
+            Err(ValidationError::NegativePrice(-1.0))
+        );
+    }
+
+    #[test]
+    fn non_finite_price_is_rejected() {
+        assert!(matches!(
+            priced(f64::NAN).validate(),
+            Err(ValidationError::NonFinitePrice(price)) if price.is_nan()
+        ));
+        for price in [f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(
+                priced(price).validate(),
+                Err(ValidationError::NonFinitePrice(price))
+            );
+        }
+    }
+
+    #[test]
+    fn zero_price_is_accepted() {
+        assert_eq!(priced(0.0).validate(), Ok(()));
+    }
 }
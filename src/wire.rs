@@ -0,0 +1,146 @@
+use serde::{Serialize, de::DeserializeOwned};
+use std::{env, fmt, str::FromStr};
+
+use crate::types::{PayloadError, StockPrice};
+
+/// Selects the wire format for publisher, consumer and quote service alike.
+pub const WIRE_FORMAT_ENV: &str = "WIRE_FORMAT";
+
+/// How `StockPrice` messages are encoded on NATS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    /// MessagePack with named fields, so unknown and missing fields are
+    /// handled the same way as in JSON.
+    Msgpack,
+}
+
+impl FromStr for WireFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Ok(WireFormat::Json),
+            "msgpack" => Ok(WireFormat::Msgpack),
+            _ => Err(format!(
+                "unknown wire format '{}'; expected json or msgpack",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for WireFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireFormat::Json => write!(f, "json"),
+            WireFormat::Msgpack => write!(f, "msgpack"),
+        }
+    }
+}
+
+impl WireFormat {
+    /// Reads `WIRE_FORMAT`, defaulting to JSON when it is unset.
+    pub fn from_env() -> Result<Self, String> {
+        match env::var(WIRE_FORMAT_ENV) {
+            Ok(value) if !value.is_empty() => value.parse(),
+            _ => Ok(WireFormat::Json),
+        }
+    }
+
+    /// Encodes a tick, batch or any other payload type in this format.
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(match self {
+            WireFormat::Json => serde_json::to_vec(value)?,
+            WireFormat::Msgpack => rmp_serde::to_vec_named(value)?,
+        })
+    }
+
+    /// Parses any payload type in this format, without validation.
+    pub fn decode_as<T: DeserializeOwned>(self, payload: &[u8]) -> Result<T, PayloadError> {
+        match self {
+            WireFormat::Json => {
+                serde_json::from_slice(payload).map_err(|e| PayloadError::Malformed(e.into()))
+            }
+            WireFormat::Msgpack => {
+                rmp_serde::from_slice(payload).map_err(|e| PayloadError::Malformed(e.into()))
+            }
+        }
+    }
+
+    /// Parses and validates a `StockPrice` payload in this format.
+    pub fn decode(self, payload: &[u8]) -> Result<StockPrice, PayloadError> {
+        match self {
+            WireFormat::Json => StockPrice::from_bytes(payload),
+            WireFormat::Msgpack => self
+                .decode_as::<StockPrice>(payload)
+                .and_then(StockPrice::validated),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch::TickBatch;
+
+    const FORMATS: [WireFormat; 2] = [WireFormat::Json, WireFormat::Msgpack];
+
+    fn tick(symbol: &str, seq: u64) -> StockPrice {
+        StockPrice {
+            symbol: symbol.to_string(),
+            price: 123.45,
+            timestamp: "2026-10-14T10:00:00Z".to_string(),
+            seq,
+            publisher_id: "publisher-1".to_string(),
+        }
+    }
+
+    fn assert_same(a: &StockPrice, b: &StockPrice) {
+        assert_eq!(a.symbol, b.symbol);
+        assert_eq!(a.price, b.price);
+        assert_eq!(a.timestamp, b.timestamp);
+        assert_eq!(a.seq, b.seq);
+        assert_eq!(a.publisher_id, b.publisher_id);
+    }
+
+    #[test]
+    fn tick_round_trips() {
+        for format in FORMATS {
+            let original = tick("AAPL", 7);
+            let payload = format.encode(&original).unwrap();
+            assert_same(&format.decode(&payload).unwrap(), &original);
+        }
+    }
+
+    #[test]
+    fn batch_round_trips() {
+        for format in FORMATS {
+            let original = TickBatch {
+                ticks: vec![tick("AAPL", 1), tick("MSFT", 2)],
+            };
+            let payload = format.encode(&original).unwrap();
+            let decoded: TickBatch = format.decode_as(&payload).unwrap();
+            assert_eq!(decoded.ticks.len(), 2);
+            for (decoded, original) in decoded.ticks.iter().zip(&original.ticks) {
+                assert_same(decoded, original);
+            }
+        }
+    }
+
+    #[test]
+    fn formats_do_not_cross_decode() {
+        let json = WireFormat::Json.encode(&tick("AAPL", 1)).unwrap();
+        assert!(WireFormat::Msgpack.decode(&json).is_err());
+        let msgpack = WireFormat::Msgpack.encode(&tick("AAPL", 1)).unwrap();
+        assert!(WireFormat::Json.decode(&msgpack).is_err());
+    }
+
+    #[test]
+    fn parses_format_names() {
+        assert_eq!("JSON".parse(), Ok(WireFormat::Json));
+        assert_eq!("msgpack".parse(), Ok(WireFormat::Msgpack));
+        assert!("xml".parse::<WireFormat>().is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use tokio::{
+    net::TcpStream,
+    time::{Duration, sleep},
+};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
+
+/// Default address of the aggregator's tick stream.
+pub const DEFAULT_WS_URL: &str = "ws://127.0.0.1:3002/stream";
+
+/// Upper bound on the delay between reconnect attempts.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(4);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+struct State {
+    url: String,
+    socket: Option<Socket>,
+    /// Failed reconnect attempts since the connection was lost.
+    attempts: u32,
+    quiet: bool,
+}
+
+/// Connects to a WebSocket tick stream and returns the payload of every
+/// text or binary frame.
+///
+/// Like the NATS client, the first connection must succeed; after that a
+/// dropped connection is retried forever, immediately at first and then
+/// with a doubling delay capped at four seconds. Reconnects are reported
+/// on stderr unless `quiet` is set, e.g. while a TUI owns the terminal.
+pub async fn connect(
+    url: &str,
+    quiet: bool,
+) -> Result<impl Stream<Item = Bytes> + Send + use<>, tokio_tungstenite::tungstenite::Error> {
+    let (socket, _) = connect_async(url).await?;
+    let state = State {
+        url: url.to_string(),
+        socket: Some(socket),
+        attempts: 0,
+        quiet,
+    };
+
+    Ok(futures::stream::unfold(state, |mut state| async move {
+        loop {
+            let Some(socket) = state.socket.as_mut() else {
+                sleep(reconnect_delay(state.attempts)).await;
+                match connect_async(&state.url).await {
+                    Ok((socket, _)) => {
+                        if !state.quiet {
+                            eprintln!("✅ Reconnected to {}", state.url);
+                        }
+                        state.socket = Some(socket);
+                        state.attempts = 0;
+                    }
+                    Err(e) => {
+                        state.attempts += 1;
+                        if !state.quiet {
+                            eprintln!("⚠️  Reconnecting to {} failed: {}", state.url, e);
+                        }
+                    }
+                }
+                continue;
+            };
+
+            match socket.next().await {
+                Some(Ok(Message::Text(text))) => return Some((Bytes::from(text), state)),
+                Some(Ok(Message::Binary(data))) => return Some((data, state)),
+                // Pings are answered by tungstenite itself
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => {}
+                Some(Ok(Message::Close(_))) | None => {
+                    if !state.quiet {
+                        eprintln!("⚠️  {} closed the connection, reconnecting...", state.url);
+                    }
+                    state.socket = None;
+                }
+                Some(Err(e)) => {
+                    if !state.quiet {
+                        eprintln!(
+                            "⚠️  Lost connection to {}: {}, reconnecting...",
+                            state.url, e
+                        );
+                    }
+                    state.socket = None;
+                }
+            }
+        }
+    }))
+}
+
+fn reconnect_delay(attempts: u32) -> Duration {
+    if attempts == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_millis(100)
+        .saturating_mul(2u32.saturating_pow(attempts - 1))
+        .min(MAX_RECONNECT_DELAY)
+}